@@ -41,7 +41,10 @@ use process_monitor::ProcessMonitor;
 
 use crate::{
     Extension,
-    api::{trigger_init_cpu_freq, trigger_reset_cpu_freq},
+    api::{
+        snapshot::{PolicySnapshot, SNAPSHOT},
+        trigger_init_cpu_freq, trigger_reset_cpu_freq,
+    },
     file_handler::FileHandler,
 };
 use cpu_info::Info;
@@ -76,6 +79,15 @@ impl Controller {
                 .map(|cpu| (cpu.policy, AtomicBool::new(false)))
                 .collect()
         });
+        SNAPSHOT.write().policies = cpu_infos
+            .iter()
+            .map(|cpu| PolicySnapshot {
+                policy: cpu.policy,
+                freqs: cpu.freqs.clone(),
+                cur_freq: cpu.read_freq(),
+                fas_freq: None,
+            })
+            .collect();
 
         #[cfg(debug_assertions)]
         debug!("cpu infos: {cpu_infos:?}");
@@ -154,6 +166,12 @@ impl Controller {
         self.reset_all_cpu_freq();
         self.process_monitor.set_pid(None);
         self.util_max = None;
+
+        let mut snapshot = SNAPSHOT.write();
+        snapshot.util_max = 0.0;
+        for policy in &mut snapshot.policies {
+            policy.fas_freq = None;
+        }
     }
 
     pub fn fas_update_freq(&mut self, control: isize, is_janked: bool) {
@@ -161,6 +179,35 @@ impl Controller {
         debug!("change freq: {control}");
 
         let fas_freqs = self.compute_target_frequencies(control, is_janked);
+        self.write_fas_freqs(fas_freqs, true);
+    }
+
+    // Frequencies decided by an extension, they still go through the extra policies.
+    pub fn fas_set_freqs(&mut self, freqs: &HashMap<i32, isize>) {
+        #[cfg(debug_assertions)]
+        debug!("set freqs: {freqs:?}");
+
+        self.publish_cur_freqs();
+        self.update_util_max();
+
+        let fas_freqs = self
+            .cpu_infos
+            .iter()
+            .map(|cpu| {
+                (
+                    cpu.policy,
+                    freqs
+                        .get(&cpu.policy)
+                        .copied()
+                        .unwrap_or(cpu.cur_fas_freq)
+                        .clamp(0, self.max_freq),
+                )
+            })
+            .collect();
+        self.write_fas_freqs(fas_freqs, false);
+    }
+
+    fn write_fas_freqs(&mut self, fas_freqs: HashMap<i32, isize>, bind_policies: bool) {
         let sorted_policies = self.sort_policies_topologically();
         let fas_freqs = Self::apply_absolute_constraints(fas_freqs, &sorted_policies);
         let fas_freqs = Self::apply_relative_constraints(fas_freqs, &sorted_policies);
@@ -172,7 +219,7 @@ impl Controller {
             all_cores
         });
 
-        if no_extra_policy() {
+        if bind_policies && no_extra_policy() {
             let fas_freq_max = fas_freqs.values().max().copied().unwrap();
             for cpu in &mut self.cpu_infos {
                 if let Some(freq) = fas_freqs.get(&cpu.policy).copied() {
@@ -190,6 +237,28 @@ impl Controller {
                 }
             }
         }
+
+        let mut snapshot = SNAPSHOT.write();
+        snapshot.util_max = self.util_max();
+        for (policy, cpu) in snapshot.policies.iter_mut().zip(&self.cpu_infos) {
+            policy.fas_freq = Some(cpu.cur_fas_freq);
+        }
+    }
+
+    // Returns the highest current frequency.
+    pub fn publish_cur_freqs(&self) -> isize {
+        let cur_freqs: Vec<_> = self
+            .cpu_infos
+            .iter()
+            .map(cpu_info::Info::read_freq)
+            .collect();
+        let cur_freq_max = cur_freqs.iter().max().copied().unwrap_or_default();
+
+        for (policy, cur_freq) in SNAPSHOT.write().policies.iter_mut().zip(cur_freqs) {
+            policy.cur_freq = cur_freq;
+        }
+
+        cur_freq_max
     }
 
     fn update_util_max(&mut self) {
@@ -209,12 +278,7 @@ impl Controller {
             .map(|cpu| cpu.cur_fas_freq)
             .max()
             .unwrap_or_default();
-        let cur_freq_max = self
-            .cpu_infos
-            .iter()
-            .map(cpu_info::Info::read_freq)
-            .max()
-            .unwrap_or_default();
+        let cur_freq_max = self.publish_cur_freqs();
 
         if is_janked {
             self.util_max = None;
@@ -417,6 +481,26 @@ impl Controller {
         }
     }
 
+    // Whether moving `fas_freq` in the direction of `control` would be clamped away entirely.
+    pub fn freq_saturated(&self, control: isize) -> bool {
+        let cur_fas_freq_max = self
+            .cpu_infos
+            .iter()
+            .map(|cpu| cpu.cur_fas_freq)
+            .max()
+            .unwrap_or_default();
+        let min_freq = self
+            .cpu_infos
+            .iter()
+            .filter_map(|cpu| cpu.freqs.first())
+            .max()
+            .copied()
+            .unwrap_or_default();
+
+        (control > 0 && cur_fas_freq_max >= self.max_freq)
+            || (control < 0 && cur_fas_freq_max <= min_freq)
+    }
+
     pub fn util_max(&self) -> f64 {
         self.util_max.unwrap_or_default()
     }
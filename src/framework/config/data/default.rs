@@ -15,7 +15,7 @@
 // You should have received a copy of the GNU General Public License along
 // with fas-rs. If not, see <https://www.gnu.org/licenses/>.
 
-use super::Config;
+use super::{Config, JankConfig, ModeConfig};
 
 impl Config {
     pub const fn default_value_keep_std() -> bool {
@@ -25,4 +25,39 @@ impl Config {
     pub const fn default_value_scene_game_list() -> bool {
         true
     }
+
+    pub const fn default_value_extension_memory_limit() -> u64 {
+        32
+    }
+
+    pub const fn default_value_extension_callback_timeout() -> u64 {
+        100
+    }
+
+    pub const fn default_value_extension_max_failures() -> u32 {
+        10
+    }
+}
+
+impl ModeConfig {
+    pub const fn default_value_kp() -> f64 {
+        0.000_3
+    }
+
+    pub fn default_value_steps() -> Vec<(f64, isize)> {
+        vec![(-5.0, -50_000), (-1.0, 0), (2.0, 50_000), (10.0, 200_000)]
+    }
+}
+
+impl Default for JankConfig {
+    fn default() -> Self {
+        Self {
+            fps_below: Some(2.0),
+            low_1: None,
+            low_01: None,
+            over_1_5x: None,
+            over_2x: None,
+            variance: None,
+        }
+    }
 }
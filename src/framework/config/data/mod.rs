@@ -26,6 +26,12 @@ use toml::Table;
 pub struct ConfigData {
     pub config: Config,
     pub game_list: Table,
+    // `[extensions.<name>]`, handed to the extension as is
+    #[serde(default, skip_serializing_if = "Table::is_empty")]
+    pub extensions: Table,
+    // package -> strategy, for A/B testing a strategy on a single game
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub game_strategy: HashMap<String, Strategy>,
     #[serde(skip)]
     pub scene_game_list: HashSet<String>,
     pub powersave: ModeConfig,
@@ -35,18 +41,63 @@ pub struct ConfigData {
 }
 
 #[allow(clippy::struct_excessive_bools)]
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
     #[serde(default = "Config::default_value_keep_std")]
     pub keep_std: bool,
     #[serde(default = "Config::default_value_scene_game_list")]
     pub scene_game_list: bool,
+    #[serde(default = "Config::default_value_extension_memory_limit")]
+    pub extension_memory_limit: u64,
+    #[serde(default = "Config::default_value_extension_callback_timeout")]
+    pub extension_callback_timeout: u64,
+    #[serde(default = "Config::default_value_extension_max_failures")]
+    pub extension_max_failures: u32,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ModeConfig {
     pub margin_fps: MarginFps,
     pub core_temp_thresh: TemperatureThreshold,
+    #[serde(default)]
+    pub revert_after: ModeDuration,
+    #[serde(default = "ModeConfig::default_value_kp")]
+    pub kp: f64,
+    #[serde(default)]
+    pub ki: f64,
+    #[serde(default)]
+    pub kd: f64,
+    #[serde(default)]
+    pub strategy: Strategy,
+    // `[error %, step khz]`, sorted by error
+    #[serde(default = "ModeConfig::default_value_steps")]
+    pub steps: Vec<(f64, isize)>,
+    #[serde(default)]
+    pub jank: JankConfig,
+}
+
+// Janked as soon as any of the given criteria holds, all of them over the frame window.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct JankConfig {
+    // Average fps this far below the target
+    pub fps_below: Option<f64>,
+    // 1% / 0.1% low under this share of the target fps
+    pub low_1: Option<f64>,
+    pub low_01: Option<f64>,
+    // At least this many frames over 1.5x / 2x the target frametime
+    pub over_1_5x: Option<usize>,
+    pub over_2x: Option<usize>,
+    // Frametime variance above this, in ms²
+    pub variance: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Strategy {
+    #[default]
+    P,
+    Pid,
+    Step,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
@@ -57,6 +108,17 @@ pub enum TemperatureThreshold {
     Temp(u64),
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ModeDuration {
+    #[default]
+    #[serde(rename = "never")]
+    Permanent,
+    #[serde(rename = "game")]
+    GameSession,
+    #[serde(untagged)]
+    Secs(u64),
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum MarginFps {
     #[serde(untagged)]
@@ -15,7 +15,7 @@
 // You should have received a copy of the GNU General Public License along
 // with fas-rs. If not, see <https://www.gnu.org/licenses/>.
 
-use std::sync::mpsc::Receiver;
+use std::{mem, sync::mpsc::Receiver};
 
 use super::data::ConfigData;
 
@@ -23,18 +23,29 @@ use super::data::ConfigData;
 pub struct Inner {
     rx: Receiver<ConfigData>,
     config: ConfigData,
+    extensions_changed: bool,
 }
 
 impl Inner {
     pub const fn new(config: ConfigData, rx: Receiver<ConfigData>) -> Self {
-        Self { rx, config }
+        Self {
+            rx,
+            config,
+            extensions_changed: false,
+        }
     }
 
     pub fn config(&mut self) -> &mut ConfigData {
         if let Some(config) = self.rx.try_iter().last() {
+            self.extensions_changed |= config.extensions != self.config.extensions;
             self.config = config;
         }
 
         &mut self.config
     }
+
+    pub fn take_extensions_changed(&mut self) -> bool {
+        self.config();
+        mem::take(&mut self.extensions_changed)
+    }
 }
@@ -30,6 +30,10 @@ struct ConfigData {
     pub balance: Table,
     pub performance: Table,
     pub fast: Table,
+    #[serde(default, skip_serializing_if = "Table::is_empty")]
+    pub extensions: Table,
+    #[serde(default, skip_serializing_if = "Table::is_empty")]
+    pub game_strategy: Table,
 }
 
 impl Config {
@@ -56,6 +60,8 @@ impl Config {
                 balance: std_conf.balance,
                 performance: std_conf.performance,
                 fast: std_conf.fast,
+                extensions: local_conf.extensions,
+                game_strategy: local_conf.game_strategy,
             };
             return Ok(toml::to_string(&new_conf)?);
         }
@@ -73,6 +79,8 @@ impl Config {
             balance,
             performance,
             fast,
+            extensions: local_conf.extensions,
+            game_strategy: local_conf.game_strategy,
         };
 
         Ok(toml::to_string(&new_conf)?)
@@ -20,14 +20,16 @@ mod inner;
 mod merge;
 mod read;
 
-use std::{fs, path::Path, sync::mpsc, thread};
+use std::{fs, path::Path, sync::mpsc, thread, time::Duration};
 
 use inner::Inner;
 use log::{error, info};
-use toml::Value;
+use toml::{Table, Value};
 
-use crate::framework::{error::Result, node::Mode};
-pub use data::{ConfigData, MarginFps, ModeConfig, TemperatureThreshold};
+use crate::framework::{error::Result, extension::Sandbox, node::Mode};
+pub use data::{
+    ConfigData, JankConfig, MarginFps, ModeConfig, ModeDuration, Strategy, TemperatureThreshold,
+};
 use read::wait_and_read;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -134,4 +136,41 @@ impl Config {
             Mode::Fast => &self.inner.config().fast,
         }
     }
+
+    // `[game_strategy]` wins over the `strategy` of the mode.
+    #[must_use]
+    pub fn strategy(&mut self, pkg: &str, m: Mode) -> Strategy {
+        let pkg = pkg.split(':').next().unwrap_or(pkg);
+
+        self.inner
+            .config()
+            .game_strategy
+            .get(pkg)
+            .copied()
+            .unwrap_or_else(|| self.mode_config(m).strategy)
+    }
+
+    #[must_use]
+    pub fn extension_sandbox(&mut self) -> Sandbox {
+        let config = &self.inner.config().config;
+
+        Sandbox {
+            allow_unsafe: Sandbox::unsafe_allowed(),
+            memory_limit: (config.extension_memory_limit * 1024 * 1024) as usize,
+            callback_timeout: Duration::from_millis(config.extension_callback_timeout),
+            max_failures: config.extension_max_failures,
+        }
+    }
+
+    #[must_use]
+    pub fn extension_settings(&mut self) -> Table {
+        self.inner.config().extensions.clone()
+    }
+
+    // `Some` once for every change of the `[extensions]` section.
+    pub fn extension_settings_changed(&mut self) -> Option<Table> {
+        self.inner
+            .take_extensions_changed()
+            .then(|| self.extension_settings())
+    }
 }
@@ -50,6 +50,8 @@ pub enum Error {
         source: mlua::Error,
     },
     #[error(transparent)]
+    Wasm(#[from] wasmi::Error),
+    #[error(transparent)]
     Null {
         #[from]
         source: NulError,
@@ -0,0 +1,102 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{collections::HashMap, sync::mpsc::Sender};
+
+use mlua::{IntoLua, Lua, Value};
+use serde::Serialize;
+
+use super::snapshot::{PolicySnapshot, SNAPSHOT};
+
+// What `compute_control(state)` gets to see, util and frequencies come from the snapshot.
+#[derive(Debug, Clone, Serialize)]
+pub struct ControlInput {
+    // Newest first, in nanoseconds
+    pub frametimes: Vec<u64>,
+    pub target_fps: f64,
+    pub margin_fps: f64,
+    pub fps_short: f64,
+    pub fps_long: f64,
+    pub is_janked: bool,
+}
+
+impl IntoLua for ControlInput {
+    fn into_lua(self, lua: &Lua) -> mlua::Result<Value> {
+        let snapshot = SNAPSHOT.read().clone();
+        let table = lua.create_table()?;
+        table.set("frametimes", self.frametimes)?;
+        table.set("target_fps", self.target_fps)?;
+        table.set("margin_fps", self.margin_fps)?;
+        table.set("fps_short", self.fps_short)?;
+        table.set("fps_long", self.fps_long)?;
+        table.set("is_janked", self.is_janked)?;
+        table.set("util", snapshot.util_max)?;
+        table.set("policies", snapshot.policies)?;
+        Ok(Value::Table(table))
+    }
+}
+
+impl ControlInput {
+    // The same fields for wasm, as toml text.
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        #[derive(Serialize)]
+        struct State<'a> {
+            #[serde(flatten)]
+            input: &'a ControlInput,
+            util: f64,
+            policies: Vec<PolicySnapshot>,
+        }
+
+        let snapshot = SNAPSHOT.read().clone();
+        toml::to_string(&State {
+            input: self,
+            util: snapshot.util_max,
+            policies: snapshot.policies,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ControlDecision {
+    // Same meaning as the built-in controller output, in khz
+    Control(isize),
+    // Policy => frequency, policies left out keep their frequency
+    Freqs(HashMap<i32, isize>),
+}
+
+impl ControlDecision {
+    // `nil` falls back to the built-in controller.
+    pub fn from_lua(value: Value) -> mlua::Result<Option<Self>> {
+        match value {
+            Value::Nil => Ok(None),
+            Value::Integer(control) => Ok(Some(Self::Control(control as isize))),
+            Value::Number(control) => Ok(Some(Self::Control(control as isize))),
+            Value::Table(freqs) => Ok(Some(Self::Freqs(
+                freqs.pairs::<i32, isize>().collect::<mlua::Result<_>>()?,
+            ))),
+            _ => Err(mlua::Error::runtime(
+                "compute_control must return a number, a table of policy frequencies or nil",
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ControlRequest {
+    pub input: ControlInput,
+    pub reply: Sender<Option<ControlDecision>>,
+}
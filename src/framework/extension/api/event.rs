@@ -0,0 +1,128 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use libc::pid_t;
+use serde::Deserialize;
+
+use super::{
+    super::{
+        core::{ExtensionList, LoadedExtension, Runtime},
+        wasm::WasmArg,
+    },
+    misc::do_callback,
+};
+
+pub const API_VERSION: u8 = 5;
+
+// How the queue treats an event when extensions fall behind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    // Never dropped
+    Lifecycle,
+    // Only the latest value matters
+    State,
+    // Dropped and counted when the queue is full
+    Sample,
+}
+
+// One line per event: variant(args) => lua callback name, first api version that receives it, kind.
+macro_rules! events {
+    ($($event: ident $(($($arg: ident: $ty: ty),*))? => $callback: literal, since $since: literal, $kind: ident;)*) => {
+        #[derive(Debug, Clone)]
+        pub enum Event {
+            $($event $(($($ty),*))?,)*
+        }
+
+        impl Event {
+            pub const fn since(&self) -> u8 {
+                match self {
+                    $(Self::$event { .. } => $since,)*
+                }
+            }
+
+            pub const fn kind(&self) -> EventKind {
+                match self {
+                    $(Self::$event { .. } => EventKind::$kind,)*
+                }
+            }
+
+            // The event a scripted `[callback, args...]` line of `ext-test` stands for.
+            pub fn from_script(callback: &str, args: &[toml::Value]) -> Result<Self> {
+                #[allow(unused_mut, unused_variables)]
+                let mut args = args.iter().cloned();
+                match callback {
+                    $($callback => Ok(Self::$event $(($(
+                        <$ty>::deserialize(args.next().with_context(|| {
+                            format!("{callback} is missing its {} argument", stringify!($arg))
+                        })?)?
+                    ),*))?),)*
+                    _ => bail!("Unknown event: {callback}"),
+                }
+            }
+
+            pub fn dispatch(&self, ext: &mut ExtensionList) {
+                for (extension, loaded) in ext.iter_mut() {
+                    self.dispatch_to(extension, loaded);
+                }
+            }
+
+            pub fn dispatch_to(&self, extension: &Path, loaded: &mut LoadedExtension) {
+                if !self.wanted_by(loaded) {
+                    return;
+                }
+
+                match &loaded.runtime {
+                    Runtime::Lua(lua) => match self.clone() {
+                        $(Self::$event $(($($arg),*))? => {
+                            do_callback(extension, lua, $callback, ($($($arg,)*)?));
+                        })*
+                    },
+                    Runtime::Wasm(wasm) => match self.clone() {
+                        $(Self::$event $(($($arg),*))? => {
+                            wasm.call(extension, $callback, &[$($(WasmArg::from($arg)),*)?]);
+                        })*
+                    },
+                }
+            }
+        }
+    };
+}
+
+events! {
+    LoadFas(pid: pid_t, pkg: String) => "load_fas", since 0, Lifecycle;
+    UnloadFas(pid: pid_t, pkg: String) => "unload_fas", since 0, Lifecycle;
+    StartFas => "start_fas", since 0, Lifecycle;
+    StopFas => "stop_fas", since 0, Lifecycle;
+    InitCpuFreq => "init_cpu_freq", since 0, Lifecycle;
+    ResetCpuFreq => "reset_cpu_freq", since 0, Lifecycle;
+    TargetFpsChange(target_fps: u32, pkg: String) => "target_fps_change", since 2, State;
+    Frame(frametime_ns: u64, fps_short: f64, fps_long: f64, target_fps: u32) => "on_frame", since 5, Sample;
+    Jank(frametime_ns: u64, target_fps: u32) => "on_jank", since 5, Sample;
+    ModeChange(old: String, new: String) => "mode_change", since 5, Lifecycle;
+    ThermalOffsetChange(offset: f64, temperature: u64) => "thermal_offset_change", since 5, State;
+    TopAppChange(pid: pid_t, pkg: String, is_game: bool) => "top_app_change", since 5, State;
+}
+
+impl Event {
+    fn wanted_by(&self, extension: &mut LoadedExtension) -> bool {
+        (self.since()..=API_VERSION).contains(&extension.api_version)
+            && (!matches!(self, Self::Frame(..)) || extension.frame_due())
+    }
+}
@@ -15,16 +15,29 @@
 // You should have received a copy of the GNU General Public License along
 // with fas-rs. If not, see <https://www.gnu.org/licenses/>.
 
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::{
+    path::Path,
+    str::FromStr,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
 
 use anyhow::Context;
 #[cfg(debug_assertions)]
 use log::debug;
 use log::warn;
 
-use crate::cpu_common::{
-    EXTRA_POLICY_MAP, IGNORE_MAP,
-    extra_policy::{AbsRangeBound, ExtraPolicy, RelRangeBound},
+use super::{
+    request::{Request, Requester},
+    snapshot::{PolicySnapshot, SNAPSHOT},
+    sysfs,
+};
+use crate::{
+    cpu_common::{
+        EXTRA_POLICY_MAP, IGNORE_MAP,
+        extra_policy::{AbsRangeBound, ExtraPolicy, RelRangeBound},
+    },
+    framework::{config::ModeDuration, node::Mode},
 };
 
 static WARNING_FLAG: AtomicBool = AtomicBool::new(false);
@@ -107,3 +120,82 @@ pub fn set_ignore_policy(policy: i32, val: bool) {
         .unwrap()
         .store(val, Ordering::Release);
 }
+
+pub fn get_mode() -> String {
+    SNAPSHOT.read().mode.to_string()
+}
+
+pub fn get_target_fps() -> Option<u32> {
+    SNAPSHOT.read().target_fps
+}
+
+pub fn get_current_fps() -> Option<f64> {
+    SNAPSHOT.read().current_fps
+}
+
+pub fn get_policies() -> Vec<PolicySnapshot> {
+    SNAPSHOT.read().policies.clone()
+}
+
+pub fn get_policy_freq(policy: i32) -> Option<isize> {
+    SNAPSHOT
+        .read()
+        .policies
+        .iter()
+        .find(|snapshot| snapshot.policy == policy)
+        .map(|snapshot| snapshot.cur_freq)
+}
+
+pub fn get_util_max() -> f64 {
+    SNAPSHOT.read().util_max
+}
+
+pub fn get_core_temperature() -> u64 {
+    SNAPSHOT.read().core_temperature
+}
+
+pub fn set_mode(requester: &Requester, mode: &str, duration: Option<&str>) -> mlua::Result<()> {
+    let mode = Mode::from_str(mode).map_err(|_| mlua::Error::runtime("Unknown mode!"))?;
+    let duration = duration
+        .map(ModeDuration::from_str)
+        .transpose()
+        .map_err(|_| mlua::Error::runtime("Illegal mode duration!"))?;
+
+    requester.send(Request::SetMode(mode, duration));
+    Ok(())
+}
+
+pub fn override_target_fps(requester: &Requester, target_fps: Option<u32>) {
+    requester.send(Request::TargetFpsOverride(
+        target_fps.filter(|target_fps| *target_fps != 0),
+    ));
+}
+
+pub fn set_margin_fps(requester: &Requester, margin_fps: Option<f64>) -> mlua::Result<()> {
+    if margin_fps.is_some_and(|margin_fps| !margin_fps.is_finite() || margin_fps < 0.0) {
+        return Err(mlua::Error::runtime("margin_fps must be positive"));
+    }
+
+    // `-0.0` passes the check above but is not sign positive
+    requester.send(Request::MarginFps(margin_fps.map(f64::abs)));
+    Ok(())
+}
+
+pub fn pause_fas(requester: &Requester, seconds: Option<u64>) {
+    requester.send(Request::Pause(seconds.map(Duration::from_secs)));
+}
+
+pub fn resume_fas(requester: &Requester) {
+    requester.send(Request::Resume);
+}
+
+pub fn write_node(path: &str, value: &str) -> mlua::Result<()> {
+    sysfs::write_node(Path::new(path), value)
+        .map_err(|e| mlua::Error::runtime(format!("Failed to write node: {e}")))
+}
+
+pub fn read_node(path: &str) -> mlua::Result<String> {
+    sysfs::read_node(Path::new(path))
+        .map(|value| value.trim().to_string())
+        .map_err(|e| mlua::Error::runtime(format!("Failed to read node: {e}")))
+}
@@ -17,10 +17,10 @@
 
 use std::path::Path;
 
-use likely_stable::LikelyResult;
-use log::error;
 use mlua::{Function, IntoLuaMulti, Lua};
 
+use crate::framework::extension::health;
+
 pub fn get_api_version(lua: &Lua) -> u8 {
     lua.globals().get("API_VERSION").unwrap_or(0)
 }
@@ -31,15 +31,7 @@ where
     S: AsRef<str>,
     A: IntoLuaMulti,
 {
-    let function = function.as_ref();
-    let extension = extension.as_ref();
-
-    if let Ok(func) = lua.globals().get::<Function>(function) {
-        func.call(args).unwrap_or_else_likely(|e| {
-            error!(
-                "Got an error when executing extension '{}', reason: {e:#?}",
-                extension.display()
-            );
-        });
+    if let Ok(func) = lua.globals().get::<Function>(function.as_ref()) {
+        health::call(extension, lua, || func.call::<()>(args));
     }
 }
@@ -15,82 +15,79 @@
 // You should have received a copy of the GNU General Public License along
 // with fas-rs. If not, see <https://www.gnu.org/licenses/>.
 
+pub mod control;
+pub mod event;
 pub mod helper_funs;
 pub mod misc;
-pub mod v0;
-pub mod v1;
-pub mod v2;
-pub mod v3;
-pub mod v4;
+pub mod request;
+pub mod snapshot;
+pub mod sysfs;
 
-use super::{Extension, core::ExtensionMap};
-pub use v0::ApiV0;
-use v1::ApiV1;
-use v2::ApiV2;
-use v3::ApiV3;
-use v4::ApiV4;
+use std::time::Duration;
 
-pub trait Api: Send {
-    fn handle_api(&self, ext: &ExtensionMap);
-
-    fn into_box(self) -> Box<dyn Api>
-    where
-        Self: Sized + 'static,
-    {
-        Box::new(self)
-    }
-}
+use super::Extension;
+use crate::framework::node::Mode;
+pub use event::Event;
 
 pub fn trigger_init_cpu_freq(extension: &Extension) {
-    extension.trigger_extentions(ApiV0::InitCpuFreq);
-    extension.trigger_extentions(ApiV1::InitCpuFreq);
-    extension.trigger_extentions(ApiV2::InitCpuFreq);
-    extension.trigger_extentions(ApiV3::InitCpuFreq);
-    extension.trigger_extentions(ApiV4::InitCpuFreq);
+    extension.trigger_extentions(Event::InitCpuFreq);
 }
 
 pub fn trigger_reset_cpu_freq(extension: &Extension) {
-    extension.trigger_extentions(ApiV0::ResetCpuFreq);
-    extension.trigger_extentions(ApiV1::ResetCpuFreq);
-    extension.trigger_extentions(ApiV2::ResetCpuFreq);
-    extension.trigger_extentions(ApiV3::ResetCpuFreq);
-    extension.trigger_extentions(ApiV4::ResetCpuFreq);
+    extension.trigger_extentions(Event::ResetCpuFreq);
 }
 
 pub fn trigger_load_fas(extension: &Extension, pid: i32, pkg: String) {
-    extension.trigger_extentions(ApiV0::LoadFas(pid, pkg.clone()));
-    extension.trigger_extentions(ApiV1::LoadFas(pid, pkg.clone()));
-    extension.trigger_extentions(ApiV2::LoadFas(pid, pkg.clone()));
-    extension.trigger_extentions(ApiV3::LoadFas(pid, pkg.clone()));
-    extension.trigger_extentions(ApiV4::LoadFas(pid, pkg));
+    extension.trigger_extentions(Event::LoadFas(pid, pkg));
 }
 
 pub fn trigger_unload_fas(extension: &Extension, pid: i32, pkg: String) {
-    extension.trigger_extentions(ApiV0::UnloadFas(pid, pkg.clone()));
-    extension.trigger_extentions(ApiV1::UnloadFas(pid, pkg.clone()));
-    extension.trigger_extentions(ApiV2::UnloadFas(pid, pkg.clone()));
-    extension.trigger_extentions(ApiV3::UnloadFas(pid, pkg.clone()));
-    extension.trigger_extentions(ApiV4::UnloadFas(pid, pkg));
+    extension.trigger_extentions(Event::UnloadFas(pid, pkg));
 }
 
 pub fn trigger_start_fas(extension: &Extension) {
-    extension.trigger_extentions(ApiV0::StartFas);
-    extension.trigger_extentions(ApiV1::StartFas);
-    extension.trigger_extentions(ApiV2::StartFas);
-    extension.trigger_extentions(ApiV3::StartFas);
-    extension.trigger_extentions(ApiV4::StartFas);
+    extension.trigger_extentions(Event::StartFas);
 }
 
 pub fn trigger_stop_fas(extension: &Extension) {
-    extension.trigger_extentions(ApiV0::StopFas);
-    extension.trigger_extentions(ApiV1::StopFas);
-    extension.trigger_extentions(ApiV2::StopFas);
-    extension.trigger_extentions(ApiV3::StopFas);
-    extension.trigger_extentions(ApiV4::StopFas);
+    extension.trigger_extentions(Event::StopFas);
 }
 
 pub fn trigger_target_fps_change(extension: &Extension, target_fps: u32, pkg: String) {
-    extension.trigger_extentions(ApiV2::TargetFpsChange(target_fps, pkg.clone()));
-    extension.trigger_extentions(ApiV3::TargetFpsChange(target_fps, pkg.clone()));
-    extension.trigger_extentions(ApiV4::TargetFpsChange(target_fps, pkg));
+    extension.trigger_extentions(Event::TargetFpsChange(target_fps, pkg));
+}
+
+pub fn trigger_frame(
+    extension: &Extension,
+    frametime: Duration,
+    fps_short: f64,
+    fps_long: f64,
+    target_fps: u32,
+) {
+    if extension.frame_due() {
+        extension.trigger_extentions(Event::Frame(
+            frametime.as_nanos() as u64,
+            fps_short,
+            fps_long,
+            target_fps,
+        ));
+    }
+}
+
+pub fn trigger_jank(extension: &Extension, frametime: Duration, target_fps: u32) {
+    if extension.jank_listened() {
+        extension.trigger_extentions(Event::Jank(frametime.as_nanos() as u64, target_fps));
+    }
+}
+
+pub fn trigger_mode_change(extension: &Extension, old: Mode, new: Mode) {
+    extension.trigger_extentions(Event::ModeChange(old.to_string(), new.to_string()));
+}
+
+pub fn trigger_thermal_offset_change(extension: &Extension, offset: f64, temperature: u64) {
+    extension.trigger_extentions(Event::ThermalOffsetChange(offset, temperature));
+}
+
+pub fn trigger_top_app_change(extension: &Extension, pid: i32, pkg: String, is_game: bool) {
+    extension.trigger_extentions(Event::TopAppChange(pid, pkg, is_game));
 }
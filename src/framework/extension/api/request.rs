@@ -0,0 +1,46 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{sync::mpsc::Sender, time::Duration};
+
+use crate::framework::{config::ModeDuration, node::Mode};
+
+// Control requests made by extensions, applied by the looper on its next iteration.
+#[derive(Debug, Clone)]
+pub enum Request {
+    SetMode(Mode, Option<ModeDuration>),
+    TargetFpsOverride(Option<u32>),
+    MarginFps(Option<f64>),
+    Pause(Option<Duration>),
+    Resume,
+}
+
+#[derive(Debug, Clone)]
+pub struct Requester {
+    extension: String,
+    sx: Sender<(String, Request)>,
+}
+
+impl Requester {
+    pub const fn new(extension: String, sx: Sender<(String, Request)>) -> Self {
+        Self { extension, sx }
+    }
+
+    pub fn send(&self, request: Request) {
+        let _ = self.sx.send((self.extension.clone(), request));
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use mlua::{IntoLua, Lua, Value};
+use parking_lot::RwLock;
+use serde::Serialize;
+
+use crate::framework::node::Mode;
+
+// Published by the scheduler, read by the extension query functions.
+pub static SNAPSHOT: RwLock<Snapshot> = RwLock::new(Snapshot::new());
+
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub mode: Mode,
+    pub target_fps: Option<u32>,
+    pub current_fps: Option<f64>,
+    pub policies: Vec<PolicySnapshot>,
+    pub util_max: f64,
+    pub core_temperature: u64,
+}
+
+impl Snapshot {
+    const fn new() -> Self {
+        Self {
+            mode: Mode::Balance,
+            target_fps: None,
+            current_fps: None,
+            policies: Vec::new(),
+            util_max: 0.0,
+            core_temperature: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicySnapshot {
+    pub policy: i32,
+    pub freqs: Vec<isize>,
+    pub cur_freq: isize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fas_freq: Option<isize>,
+}
+
+impl IntoLua for PolicySnapshot {
+    fn into_lua(self, lua: &Lua) -> mlua::Result<Value> {
+        let table = lua.create_table()?;
+        table.set("policy", self.policy)?;
+        table.set("freqs", self.freqs)?;
+        table.set("cur_freq", self.cur_freq)?;
+        table.set("fas_freq", self.fas_freq)?;
+        Ok(Value::Table(table))
+    }
+}
@@ -0,0 +1,157 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Component, Path, PathBuf},
+    sync::LazyLock,
+};
+
+use anyhow::{Result, bail};
+#[cfg(debug_assertions)]
+use log::debug;
+use log::info;
+use parking_lot::Mutex;
+
+use crate::file_handler::FileHandler;
+
+// cpufreq is owned by the controller, so it is deliberately not listed here. Nothing that can
+// run a binary or load code, like `/proc/sys/kernel/core_pattern` or module parameters in general.
+// The cgroup hierarchies are further limited to `CGROUP_FILES`.
+const ALLOWED_PREFIXES: &[&str] = &[
+    "/sys/class/kgsl",
+    "/sys/class/devfreq",
+    "/sys/kernel/gpu",
+    "/sys/kernel/ged",
+    "/sys/kernel/fpsgo",
+    "/sys/module/mtk_fpsgo/parameters",
+    "/sys/module/ged/parameters",
+    "/sys/module/msm_performance/parameters",
+    "/sys/module/cpu_boost/parameters",
+    "/proc/gpufreq",
+    "/proc/gpufreqv2",
+    "/dev/cpuset",
+    "/dev/stune",
+];
+const CGROUP_ROOTS: &[&str] = &["/dev/cpuset", "/dev/stune"];
+// Not `release_agent` or `notify_on_release`, which run a binary, nor `cgroup.procs` or `tasks`,
+// which move any process around, the daemon included.
+const CGROUP_FILES: &[&str] = &["cpus", "schedtune.boost", "schedtune.prefer_idle"];
+
+// The class directories are full of symlinks into /sys/devices, so a prefix also covers where its
+// entries point, but nothing reached through further links like `device` or `subsystem`.
+static ALLOWED_ROOTS: LazyLock<Vec<PathBuf>> = LazyLock::new(|| {
+    ALLOWED_PREFIXES
+        .iter()
+        .flat_map(|prefix| {
+            let entries = fs::read_dir(prefix)
+                .into_iter()
+                .flatten()
+                .filter_map(std::result::Result::ok)
+                .filter_map(|entry| fs::canonicalize(entry.path()).ok());
+            fs::canonicalize(prefix).ok().into_iter().chain(entries)
+        })
+        .collect()
+});
+
+static NODE_WRITER: LazyLock<Mutex<NodeWriter>> = LazyLock::new(|| Mutex::new(NodeWriter::new()));
+
+struct NodeWriter {
+    file_handler: FileHandler,
+    originals: HashMap<PathBuf, String>,
+}
+
+impl NodeWriter {
+    fn new() -> Self {
+        Self {
+            file_handler: FileHandler::new(),
+            originals: HashMap::new(),
+        }
+    }
+
+    fn write(&mut self, path: &Path, value: &str) -> Result<()> {
+        if !self.originals.contains_key(path) {
+            let original = fs::read_to_string(path)?;
+            self.originals
+                .insert(path.to_path_buf(), original.trim().to_string());
+        }
+
+        self.file_handler.write_with_workround(path, value)
+    }
+
+    fn restore(&mut self) {
+        for (path, value) in self.originals.drain() {
+            #[cfg(debug_assertions)]
+            debug!("restore node {}: {value}", path.display());
+
+            let _ = self.file_handler.write_with_workround(&path, value);
+        }
+    }
+}
+
+// Returns where the path really leads, which is what gets written.
+fn check_path(path: &Path) -> Result<PathBuf> {
+    if !path
+        .components()
+        .all(|component| matches!(component, Component::RootDir | Component::Normal(_)))
+    {
+        bail!(
+            "Node path must be absolute and normalized: {}",
+            path.display()
+        );
+    }
+
+    let real_path = fs::canonicalize(path)?;
+    if !ALLOWED_ROOTS.iter().any(|root| real_path.starts_with(root)) {
+        bail!("Node path is not allowed: {}", path.display());
+    }
+
+    if CGROUP_ROOTS.iter().any(|root| real_path.starts_with(root))
+        && !real_path
+            .file_name()
+            .is_some_and(|name| CGROUP_FILES.iter().any(|file| name == *file))
+    {
+        bail!(
+            "Only {CGROUP_FILES:?} may be written in cgroups: {}",
+            path.display()
+        );
+    }
+
+    Ok(real_path)
+}
+
+pub fn write_node(path: &Path, value: &str) -> Result<()> {
+    let path = check_path(path)?;
+    NODE_WRITER.lock().write(&path, value)
+}
+
+pub fn read_node(path: &Path) -> Result<String> {
+    let path = check_path(path)?;
+    Ok(fs::read_to_string(path)?)
+}
+
+pub fn restore_nodes() {
+    let mut writer = NODE_WRITER.lock();
+    if !writer.originals.is_empty() {
+        info!(
+            "Restore {} node(s) written by extensions",
+            writer.originals.len()
+        );
+        writer.restore();
+    }
+}
@@ -15,138 +15,779 @@
 // You should have received a copy of the GNU General Public License along
 // with fas-rs. If not, see <https://www.gnu.org/licenses/>.
 
-use std::{collections::HashMap, fs, path::PathBuf, sync::mpsc::Receiver, time::Duration};
+use std::{
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::Sender,
+    time::{Duration, Instant},
+};
 
 use inotify::{Inotify, WatchMask};
-use log::{debug, error, info};
-use mlua::Lua;
+use log::{debug, error, info, warn};
+use mlua::{Function, Lua};
+use toml::Table;
 
 use super::{
-    EXTENSIONS_PATH,
-    api::{Api, helper_funs},
+    Listeners,
+    api::{
+        Event,
+        control::{ControlDecision, ControlRequest},
+        event::API_VERSION,
+        helper_funs,
+        misc::get_api_version,
+        request::{Request, Requester},
+        sysfs,
+    },
+    harness::{self, Call},
+    health,
+    lifecycle::Lifecycle,
+    manifest::{self, Manifest},
+    queue::{EventQueue, Task},
+    sandbox::{self, Sandbox},
+    settings, storage, timer, trust,
+    wasm::WasmExtension,
 };
 use crate::framework::error::Result;
 
-pub type ExtensionMap = HashMap<PathBuf, Lua>;
+const DEFAULT_FRAME_INTERVAL: Duration = Duration::from_millis(100);
+const MAX_WAIT: Duration = Duration::from_secs(1);
+const STATUS_INTERVAL: Duration = Duration::from_secs(5);
 
-pub fn thread(rx: &Receiver<Box<dyn Api>>) {
-    let mut extensions = load_extensions().unwrap_or_default();
-    let mut inotify = Inotify::init().unwrap();
+// Kept in call order, see `manifest::sort`.
+pub type ExtensionList = Vec<(PathBuf, LoadedExtension)>;
 
-    inotify
-        .watches()
-        .add(
-            EXTENSIONS_PATH,
-            WatchMask::CLOSE_WRITE | WatchMask::CREATE | WatchMask::DELETE,
-        )
-        .unwrap();
+// Picked by the file extension of the script, see `is_script`.
+pub enum Runtime {
+    Lua(Lua),
+    Wasm(WasmExtension),
+}
+
+pub struct LoadedExtension {
+    pub runtime: Runtime,
+    pub manifest: Manifest,
+    pub api_version: u8,
+    frame_interval: Option<Duration>,
+    last_frame: Instant,
+}
+
+impl LoadedExtension {
+    pub(super) fn new(runtime: Runtime, manifest: Manifest) -> Self {
+        let api_version = manifest.api_version.unwrap_or_else(|| match &runtime {
+            Runtime::Lua(lua) => get_api_version(lua),
+            Runtime::Wasm(wasm) => wasm.api_version(),
+        });
+        let mut extension = Self {
+            runtime,
+            manifest,
+            api_version,
+            frame_interval: None,
+            last_frame: Instant::now(),
+        };
+
+        extension.frame_interval =
+            (api_version >= 5 && extension.has_callback("on_frame")).then(|| {
+                match &extension.runtime {
+                    Runtime::Lua(lua) => lua
+                        .globals()
+                        .get::<u64>("ON_FRAME_INTERVAL")
+                        .map_or(DEFAULT_FRAME_INTERVAL, Duration::from_millis),
+                    Runtime::Wasm(wasm) => wasm.frame_interval().unwrap_or(DEFAULT_FRAME_INTERVAL),
+                }
+            });
+
+        extension
+    }
+
+    fn has_callback(&self, name: &str) -> bool {
+        match &self.runtime {
+            Runtime::Lua(lua) => has_function(lua, name),
+            Runtime::Wasm(wasm) => wasm.has_callback(name),
+        }
+    }
+
+    fn next_timer(&self) -> Option<Instant> {
+        match &self.runtime {
+            Runtime::Lua(lua) => timer::next_due(lua),
+            Runtime::Wasm(wasm) => wasm.next_timer(),
+        }
+    }
+
+    fn run_timers(&self, path: &Path) {
+        match &self.runtime {
+            Runtime::Lua(lua) => timer::run_due(path, lua),
+            Runtime::Wasm(wasm) => wasm.run_timers(path),
+        }
+    }
+
+    pub fn frame_due(&mut self) -> bool {
+        match self.frame_interval {
+            Some(interval) if self.last_frame.elapsed() >= interval => {
+                self.last_frame = Instant::now();
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+fn has_function(lua: &Lua, name: &str) -> bool {
+    lua.globals().get::<Function>(name).is_ok()
+}
+
+pub fn thread(
+    queue: &EventQueue,
+    listeners: &Listeners,
+    request_sx: &Sender<(String, Request)>,
+    sandbox: &Sandbox,
+    dirs: &[PathBuf],
+    mut settings: Table,
+) {
+    let (mut extensions, mut failed) = load_extensions(dirs, request_sx, sandbox, &settings);
+    let mut lifecycle = Lifecycle::default();
+    update_listeners(&extensions, listeners);
+    health::write_status(&extensions, &failed);
+    let mut last_status = Instant::now();
+    let mut inotify = watch(dirs);
 
     loop {
-        if need_update(&mut inotify) {
-            extensions = load_extensions().unwrap_or_default();
+        let names = changed_scripts(&mut inotify, dirs);
+        let mut changed = Vec::new();
+        for name in &names {
+            let loaded_before = extensions.len();
+            extensions.retain(|(loaded, _)| loaded.file_name() != Some(OsStr::new(name)));
+            failed.retain(|(loaded, _)| loaded.file_name() != Some(OsStr::new(name)));
+
+            // The file may have been shadowed or uncovered, so look it up in every directory again
+            if let Some(path) = resolve(dirs, name) {
+                if let Some(extension) =
+                    load_extension(&path, request_sx, sandbox, &settings, &mut failed)
+                {
+                    extensions.push((path.clone(), extension));
+                }
+                changed.push(path);
+            } else if extensions.len() < loaded_before {
+                info!("Extension unloaded: {name}");
+            }
+        }
+
+        if !names.is_empty() {
+            extensions = manifest::sort(extensions);
+            failed.extend(manifest::drop_conflicts(&mut extensions, |path| {
+                changed.iter().any(|changed| changed == path)
+            }));
+
+            // Catch the new ones up with what the others have already seen
+            for (path, extension) in &mut extensions {
+                if changed.contains(path) {
+                    for event in lifecycle.replay() {
+                        event.dispatch_to(path, extension);
+                    }
+                }
+            }
+
+            update_listeners(&extensions, listeners);
+            health::write_status(&extensions, &failed);
+        }
+
+        match queue.pop_timeout(wait_timeout(&extensions)) {
+            Some(Task::Event(event)) => {
+                lifecycle.update(&event);
+                event.dispatch(&mut extensions);
+
+                // Nodes written by extensions only live as long as fas does
+                if matches!(event, Event::StopFas) {
+                    sysfs::restore_nodes();
+                }
+            }
+            Some(Task::Control(request)) => compute_control(&extensions, request),
+            Some(Task::Settings(new)) => {
+                for (path, extension) in &extensions {
+                    let section = settings::section(&new, path);
+                    if section != settings::section(&settings, path) {
+                        settings::notify(path, extension, &section);
+                    }
+                }
+
+                settings = new;
+            }
+            None => (),
+        }
+
+        for (path, extension) in &extensions {
+            extension.run_timers(path);
+        }
+
+        let dropped = queue.take_dropped();
+        if dropped > 0 {
+            warn!("Extensions are falling behind, dropped {dropped} frame sample event(s)");
+        }
+
+        if last_status.elapsed() >= STATUS_INTERVAL {
+            health::write_status(&extensions, &failed);
+            last_status = Instant::now();
         }
+    }
+}
 
-        if let Ok(trigger) = rx.recv_timeout(Duration::from_secs(1)) {
-            trigger.handle_api(&extensions);
+fn watch(dirs: &[PathBuf]) -> Inotify {
+    let inotify = Inotify::init().unwrap();
+
+    for dir in dirs {
+        if let Err(e) = inotify.watches().add(
+            dir,
+            WatchMask::CLOSE_WRITE
+                | WatchMask::CREATE
+                | WatchMask::DELETE
+                | WatchMask::MOVED_FROM
+                | WatchMask::MOVED_TO,
+        ) {
+            warn!("Failed to watch extension directory {}: {e}", dir.display());
         }
     }
+
+    // So that `ext-trust` or removing the allowlist takes effect without a restart
+    if let Some(dir) = trust::allowlist().parent()
+        && let Err(e) = inotify.watches().add(
+            dir,
+            WatchMask::CLOSE_WRITE
+                | WatchMask::CREATE
+                | WatchMask::DELETE
+                | WatchMask::MOVED_FROM
+                | WatchMask::MOVED_TO,
+        )
+    {
+        warn!("Failed to watch {}: {e}", dir.display());
+    }
+
+    inotify
+}
+
+// Wake up for the next timer, but still poll inotify at least once a second.
+fn wait_timeout(extensions: &ExtensionList) -> Duration {
+    extensions
+        .iter()
+        .filter_map(|(_, extension)| extension.next_timer())
+        .min()
+        .map_or(MAX_WAIT, |due| {
+            due.saturating_duration_since(Instant::now()).min(MAX_WAIT)
+        })
+}
+
+// Only the first extension in call order that defines `compute_control` gets asked.
+fn compute_control(extensions: &ExtensionList, request: ControlRequest) {
+    let decision = extensions
+        .iter()
+        .find(|(_, extension)| provides_control(extension))
+        .and_then(|(path, extension)| match &extension.runtime {
+            Runtime::Lua(lua) => {
+                let func: Function = lua.globals().get("compute_control").ok()?;
+                health::call(path, lua, || {
+                    func.call(request.input).and_then(ControlDecision::from_lua)
+                })
+                .flatten()
+            }
+            Runtime::Wasm(wasm) => wasm.compute_control(path, &request.input),
+        });
+
+    let _ = request.reply.send(decision);
+}
+
+fn provides_control(extension: &LoadedExtension) -> bool {
+    extension.api_version >= 5 && extension.has_callback("compute_control")
+}
+
+fn update_listeners(extensions: &ExtensionList, listeners: &Listeners) {
+    listeners.set_loaded(!extensions.is_empty());
+    listeners.set_frame_interval(
+        extensions
+            .iter()
+            .filter_map(|(_, extension)| extension.frame_interval)
+            .min(),
+    );
+    listeners.set_control(
+        extensions
+            .iter()
+            .any(|(_, extension)| provides_control(extension)),
+    );
+    listeners.set_jank(
+        extensions
+            .iter()
+            .any(|(_, extension)| extension.api_version >= 5 && extension.has_callback("on_jank")),
+    );
+}
+
+// File names of the scripts touched since the last call, in any of the watched directories.
+// A changed sidecar manifest counts for its script, whichever runtime that is.
+// A changed allowlist counts for every script, trusted or not.
+fn changed_scripts(inotify: &mut Inotify, dirs: &[PathBuf]) -> Vec<String> {
+    let mut buffer = [0; 1024];
+    let Ok(events) = inotify.read_events(&mut buffer) else {
+        return Vec::new();
+    };
+
+    let mut changed = Vec::new();
+    for name in events.filter_map(|event| event.name) {
+        if Some(name) == trust::allowlist().file_name() {
+            info!("Extension allowlist changed, reloading every extension");
+            for name in all_scripts(dirs) {
+                if !changed.contains(&name) {
+                    changed.push(name);
+                }
+            }
+            continue;
+        }
+
+        let name = name.to_string_lossy();
+        let names = name.strip_suffix(".manifest.toml").map_or_else(
+            || vec![name.to_string()],
+            |stem| vec![format!("{stem}.lua"), format!("{stem}.wasm")],
+        );
+
+        for name in names {
+            if is_script(Path::new(&name)) && !changed.contains(&name) {
+                changed.push(name);
+            }
+        }
+    }
+
+    changed
+}
+
+fn all_scripts(dirs: &[PathBuf]) -> Vec<String> {
+    dirs.iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(std::result::Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && is_script(path))
+        .filter_map(|path| Some(path.file_name()?.to_string_lossy().into_owned()))
+        .collect()
+}
+
+// The first directory that has the script wins.
+fn resolve(dirs: &[PathBuf], name: &str) -> Option<PathBuf> {
+    dirs.iter()
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+fn load_extensions(
+    dirs: &[PathBuf],
+    request_sx: &Sender<(String, Request)>,
+    sandbox: &Sandbox,
+    settings: &Table,
+) -> (ExtensionList, Vec<(PathBuf, String)>) {
+    let mut list = ExtensionList::new();
+    let mut failed = Vec::new();
+
+    for dir in dirs {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+
+        for path in entries
+            .filter_map(std::result::Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && is_script(path))
+        {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if let Some(shadowing) = resolve(dirs, &name).filter(|winner| *winner != path) {
+                warn!(
+                    "Extension {} is shadowed by {}, skipping it",
+                    path.display(),
+                    shadowing.display()
+                );
+                continue;
+            }
+
+            if let Some(extension) =
+                load_extension(&path, request_sx, sandbox, settings, &mut failed)
+            {
+                list.push((path, extension));
+            }
+        }
+    }
+
+    let mut list = manifest::sort(list);
+    failed.extend(manifest::drop_conflicts(&mut list, |_| true));
+
+    (list, failed)
 }
 
-fn need_update(inotify: &mut Inotify) -> bool {
-    inotify.read_events(&mut [0; 1024]).is_ok()
+fn load_extension(
+    path: &Path,
+    request_sx: &Sender<(String, Request)>,
+    sandbox: &Sandbox,
+    settings: &Table,
+    failed: &mut Vec<(PathBuf, String)>,
+) -> Option<LoadedExtension> {
+    init_extension(
+        path,
+        request_sx,
+        sandbox,
+        &settings::section(settings, path),
+    )
+    .map_err(|e| {
+        error!(
+            "Extension loading failed: {}, reason: {e:#?}",
+            path.display()
+        );
+        failed.push((path.to_path_buf(), e.to_string()));
+    })
+    .ok()
 }
 
-fn load_extensions() -> Result<ExtensionMap> {
-    let mut map: ExtensionMap = HashMap::new();
+// `settings` is the extension's own part of `[extensions]`.
+fn init_extension(
+    path: &Path,
+    request_sx: &Sender<(String, Request)>,
+    sandbox: &Sandbox,
+    settings: &Table,
+) -> Result<LoadedExtension> {
+    let code = fs::read(path)?;
+    let sidecar = fs::read_to_string(manifest::sidecar(path)).ok();
+    if trust::enforced() {
+        trust::verify(path, &code)?;
+        if let Some(sidecar) = &sidecar {
+            trust::verify(&manifest::sidecar(path), sidecar.as_bytes())?;
+        }
+    }
 
-    for file in fs::read_dir(EXTENSIONS_PATH)?
-        .map(std::result::Result::unwrap)
-        .filter(|f| f.file_type().unwrap().is_file() && f.path().extension().unwrap() == "lua")
+    if path
+        .extension()
+        .is_some_and(|extension| extension == "wasm")
     {
-        let lua = Lua::new();
-        let path = file.path();
-        let file = fs::read_to_string(&path)?;
+        let wasm = WasmExtension::new(path, &code, requester(path, request_sx), sandbox)?;
+        let manifest = Manifest::read(path, sidecar.as_deref(), None)?;
+        let extension = finish_loading(path, Runtime::Wasm(wasm), manifest);
 
-        lua.globals().set(
-            "log_info",
-            lua.create_function(|_, message: String| {
-                info!("extension: {message}");
-                Ok(())
-            })?,
-        )?;
+        // Nothing like a global to put them in beforehand
+        if !settings.is_empty() {
+            settings::notify(path, &extension, settings);
+        }
 
-        lua.globals().set(
-            "log_debug",
-            lua.create_function(|_, message: String| {
-                debug!("extension: {message}");
-                Ok(())
-            })?,
-        )?;
+        return Ok(extension);
+    }
 
-        lua.globals().set(
-            "log_error",
-            lua.create_function(|_, message: String| {
-                error!("extension: {message}");
-                Ok(())
-            })?,
-        )?;
+    init_extension_with(
+        sandbox.create_lua()?,
+        path,
+        &code,
+        sidecar.as_deref(),
+        request_sx,
+        sandbox,
+        settings,
+    )
+}
 
-        // Add in api v1
-        // Removed in v4.2.0(apiv4)
-        lua.globals().set(
-            "set_policy_freq_offset",
-            lua.create_function(|_, (policy, offset)| {
-                helper_funs::set_policy_freq_offset(policy, offset);
-                Ok(())
-            })?,
-        )?;
+fn requester(path: &Path, request_sx: &Sender<(String, Request)>) -> Requester {
+    Requester::new(
+        path.file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned(),
+        request_sx.clone(),
+    )
+}
 
-        // Add in api v3
-        lua.globals().set(
-            "set_ignore_policy",
-            lua.create_function(|_, (policy, val)| {
+// `lua` may already carry app data that changes how the globals behave, see `harness`.
+pub fn init_extension_with(
+    lua: Lua,
+    path: &Path,
+    code: &[u8],
+    sidecar: Option<&str>,
+    request_sx: &Sender<(String, Request)>,
+    sandbox: &Sandbox,
+    settings: &Table,
+) -> Result<LoadedExtension> {
+    health::init(&lua, sandbox.max_failures);
+
+    register_base(&lua)?;
+    register_queries(&lua)?;
+    register_controls(&lua, requester(path, request_sx))?;
+    register_runtime(&lua, path)?;
+
+    // Add in api v5
+    lua.globals()
+        .set("EXTENSION_CONFIG", settings::to_lua_table(&lua, settings)?)?;
+
+    sandbox::guard(&lua, || lua.load(code).exec())?;
+    let manifest = Manifest::read(path, sidecar, Some(&lua))?;
+
+    Ok(finish_loading(path, Runtime::Lua(lua), manifest))
+}
+
+fn finish_loading(path: &Path, runtime: Runtime, manifest: Manifest) -> LoadedExtension {
+    info!(
+        "Extension loaded successfully: {} ({} {})",
+        path.display(),
+        manifest.name,
+        manifest.version.as_deref().unwrap_or("unknown version")
+    );
+
+    let extension = LoadedExtension::new(runtime, manifest);
+    if extension.api_version > API_VERSION {
+        warn!(
+            "Extension '{}' requires api v{}, but only up to v{API_VERSION} is supported, it will not receive any event",
+            path.display(),
+            extension.api_version
+        );
+    }
+
+    extension
+}
+
+fn register_base(lua: &Lua) -> mlua::Result<()> {
+    lua.globals().set(
+        "log_info",
+        lua.create_function(|_, message: String| {
+            info!("extension: {message}");
+            Ok(())
+        })?,
+    )?;
+
+    lua.globals().set(
+        "log_debug",
+        lua.create_function(|_, message: String| {
+            debug!("extension: {message}");
+            Ok(())
+        })?,
+    )?;
+
+    lua.globals().set(
+        "log_error",
+        lua.create_function(|_, message: String| {
+            error!("extension: {message}");
+            Ok(())
+        })?,
+    )?;
+
+    // Add in api v1
+    // Removed in v4.2.0(apiv4)
+    lua.globals().set(
+        "set_policy_freq_offset",
+        lua.create_function(|_, (policy, offset)| {
+            helper_funs::set_policy_freq_offset(policy, offset);
+            Ok(())
+        })?,
+    )?;
+
+    // Add in api v3
+    lua.globals().set(
+        "set_ignore_policy",
+        lua.create_function(|lua, (policy, val)| {
+            if !harness::record(lua, || Call::SetIgnorePolicy(policy, val)) {
                 helper_funs::set_ignore_policy(policy, val);
-                Ok(())
+            }
+            Ok(())
+        })?,
+    )?;
+
+    // Add in api v4
+    lua.globals().set(
+        "set_extra_policy_abs",
+        lua.create_function(|lua, (policy, min, max)| {
+            if !harness::record(lua, || Call::SetExtraPolicyAbs(policy, min, max)) {
+                helper_funs::set_extra_policy_abs(policy, min, max);
+            }
+            Ok(())
+        })?,
+    )?;
+
+    // Add in api v4
+    lua.globals().set(
+        "set_extra_policy_rel",
+        lua.create_function(|lua, (policy, target_policy, min, max)| {
+            if !harness::record(lua, || {
+                Call::SetExtraPolicyRel(policy, target_policy, min, max)
+            }) {
+                helper_funs::set_extra_policy_rel(policy, target_policy, min, max);
+            }
+            Ok(())
+        })?,
+    )?;
+
+    // Add in api v4
+    lua.globals().set(
+        "remove_extra_policy",
+        lua.create_function(|lua, policy| {
+            if !harness::record(lua, || Call::RemoveExtraPolicy(policy)) {
+                helper_funs::remove_extra_policy(policy);
+            }
+            Ok(())
+        })?,
+    )?;
+
+    Ok(())
+}
+
+fn register_queries(lua: &Lua) -> mlua::Result<()> {
+    // Add in api v5
+    lua.globals().set(
+        "get_mode",
+        lua.create_function(|_, ()| Ok(helper_funs::get_mode()))?,
+    )?;
+
+    // Add in api v5
+    lua.globals().set(
+        "get_target_fps",
+        lua.create_function(|_, ()| Ok(helper_funs::get_target_fps()))?,
+    )?;
+
+    // Add in api v5
+    lua.globals().set(
+        "get_current_fps",
+        lua.create_function(|_, ()| Ok(helper_funs::get_current_fps()))?,
+    )?;
+
+    // Add in api v5
+    lua.globals().set(
+        "get_policies",
+        lua.create_function(|_, ()| Ok(helper_funs::get_policies()))?,
+    )?;
+
+    // Add in api v5
+    lua.globals().set(
+        "get_policy_freq",
+        lua.create_function(|_, policy| Ok(helper_funs::get_policy_freq(policy)))?,
+    )?;
+
+    // Add in api v5
+    lua.globals().set(
+        "get_util_max",
+        lua.create_function(|_, ()| Ok(helper_funs::get_util_max()))?,
+    )?;
+
+    // Add in api v5
+    lua.globals().set(
+        "get_core_temperature",
+        lua.create_function(|_, ()| Ok(helper_funs::get_core_temperature()))?,
+    )?;
+
+    Ok(())
+}
+
+fn register_controls(lua: &Lua, requester: Requester) -> mlua::Result<()> {
+    // Add in api v5
+    {
+        let requester = requester.clone();
+        lua.globals().set(
+            "set_mode",
+            lua.create_function(move |lua, (mode, duration): (String, Option<String>)| {
+                harness::request(lua, &requester, |requester| {
+                    helper_funs::set_mode(requester, &mode, duration.as_deref())
+                })
             })?,
         )?;
+    }
 
-        // Add in api v4
+    // Add in api v5
+    {
+        let requester = requester.clone();
         lua.globals().set(
-            "set_extra_policy_abs",
-            lua.create_function(|_, (policy, min, max)| {
-                helper_funs::set_extra_policy_abs(policy, min, max);
+            "override_target_fps",
+            lua.create_function(move |lua, target_fps| {
+                harness::request(lua, &requester, |requester| {
+                    helper_funs::override_target_fps(requester, target_fps);
+                });
                 Ok(())
             })?,
         )?;
+    }
 
-        // Add in api v4
+    // Add in api v5
+    {
+        let requester = requester.clone();
         lua.globals().set(
-            "set_extra_policy_rel",
-            lua.create_function(|_, (policy, target_policy, min, max)| {
-                helper_funs::set_extra_policy_rel(policy, target_policy, min, max);
-                Ok(())
+            "set_margin_fps",
+            lua.create_function(move |lua, margin_fps| {
+                harness::request(lua, &requester, |requester| {
+                    helper_funs::set_margin_fps(requester, margin_fps)
+                })
             })?,
         )?;
+    }
 
-        // Add in api v4
+    // Add in api v5
+    {
+        let requester = requester.clone();
         lua.globals().set(
-            "remove_extra_policy",
-            lua.create_function(|_, policy| {
-                helper_funs::remove_extra_policy(policy);
+            "pause_fas",
+            lua.create_function(move |lua, seconds| {
+                harness::request(lua, &requester, |requester| {
+                    helper_funs::pause_fas(requester, seconds);
+                });
                 Ok(())
             })?,
         )?;
+    }
 
-        match lua.load(&file).exec() {
-            Ok(()) => {
-                info!("Extension loaded successfully: {}", path.display());
-                map.insert(path, lua);
-            }
-            Err(e) => {
-                error!("Extension loading failed, reason: {e:#?}");
+    // Add in api v5
+    lua.globals().set(
+        "resume_fas",
+        lua.create_function(move |lua, ()| {
+            harness::request(lua, &requester, helper_funs::resume_fas);
+            Ok(())
+        })?,
+    )?;
+
+    // Add in api v5
+    lua.globals().set(
+        "write_node",
+        lua.create_function(|lua, (path, value): (String, String)| {
+            if harness::record(lua, || Call::WriteNode(path.clone(), value.clone())) {
+                return Ok(());
             }
-        }
-    }
+            helper_funs::write_node(&path, &value)
+        })?,
+    )?;
+
+    // Add in api v5
+    lua.globals().set(
+        "read_node",
+        lua.create_function(|_, path: String| helper_funs::read_node(&path))?,
+    )?;
+
+    Ok(())
+}
+
+fn register_runtime(lua: &Lua, path: &Path) -> mlua::Result<()> {
+    timer::init(lua)?;
+    storage::init(lua, &path.file_stem().unwrap_or_default().to_string_lossy());
+
+    // Add in api v5
+    lua.globals().set(
+        "storage_get",
+        lua.create_function(|lua, key: String| storage::get(lua, &key))?,
+    )?;
+
+    // Add in api v5
+    lua.globals().set(
+        "storage_set",
+        lua.create_function(|lua, (key, value)| storage::set(lua, key, value))?,
+    )?;
+
+    // Add in api v5
+    lua.globals().set(
+        "set_interval",
+        lua.create_function(|lua, (ms, callback)| timer::set_interval(lua, ms, callback))?,
+    )?;
+
+    // Add in api v5
+    lua.globals().set(
+        "set_timeout",
+        lua.create_function(|lua, (ms, callback)| timer::set_timeout(lua, ms, callback))?,
+    )?;
+
+    // Add in api v5
+    lua.globals()
+        .set("clear_timer", lua.create_function(timer::clear_timer)?)?;
+
+    Ok(())
+}
 
-    Ok(map)
+fn is_script(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "lua" || extension == "wasm")
 }
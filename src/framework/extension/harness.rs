@@ -0,0 +1,253 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    cell::RefCell,
+    fmt::{self, Display, Formatter},
+    fs,
+    path::Path,
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, anyhow};
+use mlua::Lua;
+use serde::Deserialize;
+use toml::Table;
+
+use super::{
+    api::{
+        Event,
+        request::{Request, Requester},
+    },
+    core, health, manifest,
+    sandbox::Sandbox,
+    timer,
+};
+use crate::framework::error::Result;
+
+// `fas-rs ext-test <extension.lua> <scenario.toml>`
+//
+// events = [
+//     ["load_fas", 1234, "com.example.game"],
+//     ["target_fps_change", 60, "com.example.game"],
+//     ["sleep", 500],
+//     ["stop_fas"],
+// ]
+// expect = ["set_extra_policy_abs(7, 1200000, nil)"]
+//
+// [config] # What `[extensions.<name>]` would hold
+#[derive(Deserialize)]
+struct Scenario {
+    events: Vec<Vec<toml::Value>>,
+    expect: Option<Vec<String>>,
+    #[serde(default)]
+    config: Table,
+}
+
+// A call the extension made that would have changed the device.
+#[derive(Debug, Clone)]
+pub enum Call {
+    SetIgnorePolicy(i32, bool),
+    SetExtraPolicyAbs(i32, Option<isize>, Option<isize>),
+    SetExtraPolicyRel(i32, i32, Option<isize>, Option<isize>),
+    RemoveExtraPolicy(i32),
+    WriteNode(String, String),
+    Request(Request),
+}
+
+impl Display for Call {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::SetIgnorePolicy(policy, val) => write!(f, "set_ignore_policy({policy}, {val})"),
+            Self::SetExtraPolicyAbs(policy, min, max) => write!(
+                f,
+                "set_extra_policy_abs({policy}, {}, {})",
+                nil_or(min.as_ref()),
+                nil_or(max.as_ref())
+            ),
+            Self::SetExtraPolicyRel(policy, target_policy, min, max) => write!(
+                f,
+                "set_extra_policy_rel({policy}, {target_policy}, {}, {})",
+                nil_or(min.as_ref()),
+                nil_or(max.as_ref())
+            ),
+            Self::RemoveExtraPolicy(policy) => write!(f, "remove_extra_policy({policy})"),
+            Self::WriteNode(path, value) => write!(f, "write_node({path:?}, {value:?})"),
+            Self::Request(Request::SetMode(mode, None)) => write!(f, "set_mode(\"{mode}\")"),
+            Self::Request(Request::SetMode(mode, Some(duration))) => {
+                write!(f, "set_mode(\"{mode}\", \"{duration}\")")
+            }
+            Self::Request(Request::TargetFpsOverride(target_fps)) => {
+                write!(f, "override_target_fps({})", nil_or(target_fps.as_ref()))
+            }
+            Self::Request(Request::MarginFps(margin_fps)) => {
+                write!(f, "set_margin_fps({})", nil_or(margin_fps.as_ref()))
+            }
+            Self::Request(Request::Pause(duration)) => write!(
+                f,
+                "pause_fas({})",
+                nil_or(duration.map(|duration| duration.as_secs()).as_ref())
+            ),
+            Self::Request(Request::Resume) => write!(f, "resume_fas()"),
+        }
+    }
+}
+
+fn nil_or<T: Display>(value: Option<&T>) -> String {
+    value.map_or_else(|| "nil".into(), ToString::to_string)
+}
+
+// Installed in place of the global policy maps while testing.
+struct Recorder {
+    calls: RefCell<Vec<Call>>,
+}
+
+pub fn is_recording(lua: &Lua) -> bool {
+    lua.app_data_ref::<Recorder>().is_some()
+}
+
+// Returns false outside of `ext-test`, the caller then applies the call for real.
+pub fn record(lua: &Lua, call: impl FnOnce() -> Call) -> bool {
+    lua.app_data_ref::<Recorder>().is_some_and(|recorder| {
+        recorder.calls.borrow_mut().push(call());
+        true
+    })
+}
+
+// Control requests are caught as they are made, so they keep their place among the direct calls.
+pub fn request<R>(lua: &Lua, requester: &Requester, f: impl FnOnce(&Requester) -> R) -> R {
+    if !is_recording(lua) {
+        return f(requester);
+    }
+
+    let (sx, requests) = mpsc::channel();
+    let result = f(&Requester::new(String::new(), sx));
+    for (_, request) in requests.try_iter() {
+        record(lua, || Call::Request(request));
+    }
+
+    result
+}
+
+fn take_calls(lua: &Lua) -> Vec<Call> {
+    lua.app_data_ref::<Recorder>()
+        .map(|recorder| recorder.calls.take())
+        .unwrap_or_default()
+}
+
+pub fn run<P: AsRef<Path>>(script: P, scenario: P) -> Result<()> {
+    let script = script.as_ref();
+    let scenario: Scenario = toml::from_str(&fs::read_to_string(scenario.as_ref())?)?;
+
+    // The defaults of games.toml, except that failing callbacks never get the extension disabled
+    let sandbox = Sandbox {
+        allow_unsafe: false,
+        memory_limit: 32 * 1024 * 1024,
+        callback_timeout: Duration::from_millis(100),
+        max_failures: 0,
+    };
+    let lua = sandbox.create_lua()?;
+    lua.set_app_data(Recorder {
+        calls: RefCell::new(Vec::new()),
+    });
+
+    // Nothing is sent through it while recording, see `request`
+    let (request_sx, _) = mpsc::channel();
+    let mut extension = core::init_extension_with(
+        lua.clone(),
+        script,
+        &fs::read(script)?,
+        fs::read_to_string(manifest::sidecar(script))
+            .ok()
+            .as_deref(),
+        &request_sx,
+        &sandbox,
+        &scenario.config,
+    )?;
+    let mut recorded = take_calls(&lua);
+    print_calls("(load)", &recorded);
+
+    for line in &scenario.events {
+        let (callback, args) = line.split_first().context("Empty event line in scenario")?;
+        let callback = callback
+            .as_str()
+            .with_context(|| format!("Event name must be a string, got {callback}"))?;
+
+        if callback == "sleep" {
+            let ms = args
+                .first()
+                .and_then(toml::Value::as_integer)
+                .context("sleep needs a duration in milliseconds")?;
+            sleep(script, &lua, Duration::from_millis(ms as u64));
+        } else {
+            Event::from_script(callback, args)?.dispatch_to(script, &mut extension);
+            timer::run_due(script, &lua);
+        }
+
+        let calls = take_calls(&lua);
+        let args: Vec<_> = args.iter().map(ToString::to_string).collect();
+        print_calls(&format!("{callback}({})", args.join(", ")), &calls);
+        recorded.extend(calls);
+    }
+
+    let failures = health::failures(&lua);
+    if failures > 0 {
+        return Err(anyhow!("{failures} callback(s) of the extension failed").into());
+    }
+
+    if let Some(expect) = scenario.expect {
+        let recorded: Vec<_> = recorded.iter().map(ToString::to_string).collect();
+        if recorded != expect {
+            return Err(anyhow!(
+                "Recorded calls do not match, expected:\n{}\ngot:\n{}",
+                expect.join("\n"),
+                recorded.join("\n")
+            )
+            .into());
+        }
+
+        println!("All {} expected call(s) matched", expect.len());
+    }
+
+    Ok(())
+}
+
+// Lets time pass for the timers and the `on_frame` interval of the extension.
+fn sleep(script: &Path, lua: &Lua, duration: Duration) {
+    let deadline = Instant::now() + duration;
+
+    loop {
+        timer::run_due(script, lua);
+
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+
+        let wake = timer::next_due(lua).map_or(deadline, |due| due.min(deadline));
+        thread::sleep(wake.saturating_duration_since(now));
+    }
+}
+
+fn print_calls(event: &str, calls: &[Call]) {
+    println!("> {event}");
+    for call in calls {
+        println!("  {call}");
+    }
+}
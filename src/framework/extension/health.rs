@@ -0,0 +1,213 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    cell::Cell,
+    collections::BTreeMap,
+    fmt::Debug,
+    fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use log::error;
+use mlua::Lua;
+use serde::Serialize;
+
+use super::{
+    core::{ExtensionList, Runtime},
+    sandbox,
+};
+
+const STATUS_PATH: &str = "/dev/fas_rs/extension_status.toml";
+// Anything slower than a frame at 60hz is worth reporting
+const SLOW_CALL: Duration = Duration::from_millis(16);
+
+pub struct Health {
+    max_failures: u32,
+    calls: Cell<u64>,
+    failures: Cell<u32>,
+    slow_calls: Cell<u64>,
+    total_latency: Cell<Duration>,
+    quarantined: Cell<bool>,
+}
+
+#[derive(Serialize)]
+struct Status<'a> {
+    name: &'a str,
+    state: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<&'a str>,
+    calls: u64,
+    failures: u32,
+    slow_calls: u64,
+    average_latency_us: u64,
+}
+
+impl Health {
+    pub const fn new(max_failures: u32) -> Self {
+        Self {
+            max_failures,
+            calls: Cell::new(0),
+            failures: Cell::new(0),
+            slow_calls: Cell::new(0),
+            total_latency: Cell::new(Duration::ZERO),
+            quarantined: Cell::new(false),
+        }
+    }
+
+    fn status<'a>(&self, name: &'a str) -> Status<'a> {
+        let calls = self.calls.get();
+        let average_latency_us = if calls == 0 {
+            0
+        } else {
+            (self.total_latency.get().as_micros() / u128::from(calls)) as u64
+        };
+
+        Status {
+            name,
+            state: if self.quarantined.get() {
+                "quarantined"
+            } else {
+                "loaded"
+            },
+            reason: None,
+            calls,
+            failures: self.failures.get(),
+            slow_calls: self.slow_calls.get(),
+            average_latency_us,
+        }
+    }
+}
+
+pub fn init(lua: &Lua, max_failures: u32) {
+    lua.set_app_data(Health::new(max_failures));
+}
+
+pub fn failures(lua: &Lua) -> u32 {
+    lua.app_data_ref::<Health>()
+        .map_or(0, |health| health.failures.get())
+}
+
+// Runs a callback of the extension under its budget, and keeps the books on it.
+pub fn call<P, R>(extension: P, lua: &Lua, f: impl FnOnce() -> mlua::Result<R>) -> Option<R>
+where
+    P: AsRef<Path>,
+{
+    track(
+        extension.as_ref(),
+        || lua.app_data_ref::<Health>(),
+        || sandbox::guard(lua, f),
+    )
+}
+
+// `health` is looked up around the call rather than held, the callback may need the same app data.
+pub fn track<H, R, E>(
+    extension: &Path,
+    health: impl Fn() -> Option<H>,
+    f: impl FnOnce() -> Result<R, E>,
+) -> Option<R>
+where
+    H: Deref<Target = Health>,
+    E: Debug,
+{
+    if health().is_some_and(|health| health.quarantined.get()) {
+        return None;
+    }
+
+    let start = Instant::now();
+    let result = f();
+    let latency = start.elapsed();
+
+    if let Some(health) = health() {
+        health.calls.set(health.calls.get() + 1);
+        health
+            .total_latency
+            .set(health.total_latency.get() + latency);
+
+        if latency > SLOW_CALL {
+            health.slow_calls.set(health.slow_calls.get() + 1);
+        }
+
+        if result.is_err() {
+            health.failures.set(health.failures.get() + 1);
+
+            if health.max_failures > 0 && health.failures.get() >= health.max_failures {
+                error!(
+                    "Extension '{}' failed {} times, it is disabled until its file changes",
+                    extension.display(),
+                    health.failures.get()
+                );
+                health.quarantined.set(true);
+            }
+        }
+    }
+
+    result
+        .map_err(|e| {
+            error!(
+                "Got an error when executing extension '{}', reason: {e:#?}",
+                extension.display()
+            );
+        })
+        .ok()
+}
+
+pub fn write_status(extensions: &ExtensionList, failed: &[(PathBuf, String)]) {
+    let mut status = BTreeMap::new();
+
+    for (path, extension) in extensions {
+        let name = &extension.manifest.name;
+        let extension_status = match &extension.runtime {
+            Runtime::Lua(lua) => lua
+                .app_data_ref::<Health>()
+                .map(|health| health.status(name)),
+            Runtime::Wasm(wasm) => Some(wasm.health().status(name)),
+        };
+
+        if let Some(extension_status) = extension_status {
+            status.insert(file_name(path), extension_status);
+        }
+    }
+
+    for (path, reason) in failed {
+        status.insert(
+            file_name(path),
+            Status {
+                name: "",
+                state: "failed",
+                reason: Some(reason),
+                calls: 0,
+                failures: 0,
+                slow_calls: 0,
+                average_latency_us: 0,
+            },
+        );
+    }
+
+    if let Ok(raw) = toml::to_string(&status) {
+        let _ = fs::write(STATUS_PATH, raw);
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned()
+}
@@ -0,0 +1,78 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use libc::pid_t;
+
+use super::api::Event;
+
+// What an extension loaded right now would have seen so far.
+#[derive(Debug, Default)]
+pub struct Lifecycle {
+    game: Option<(pid_t, String)>,
+    started: bool,
+    cpu_freq_inited: bool,
+    target_fps: Option<u32>,
+    top_app: Option<(pid_t, String, bool)>,
+}
+
+impl Lifecycle {
+    pub fn update(&mut self, event: &Event) {
+        match event {
+            Event::LoadFas(pid, pkg) => self.game = Some((*pid, pkg.clone())),
+            Event::UnloadFas(..) => {
+                self.game = None;
+                self.target_fps = None;
+            }
+            Event::StartFas => self.started = true,
+            Event::StopFas => self.started = false,
+            Event::InitCpuFreq => self.cpu_freq_inited = true,
+            Event::ResetCpuFreq => self.cpu_freq_inited = false,
+            Event::TargetFpsChange(target_fps, _) => self.target_fps = Some(*target_fps),
+            Event::TopAppChange(pid, pkg, is_game) => {
+                self.top_app = Some((*pid, pkg.clone(), *is_game));
+            }
+            _ => (),
+        }
+    }
+
+    // The events to replay, in the order the scheduler sends them.
+    pub fn replay(&self) -> Vec<Event> {
+        let mut events = Vec::new();
+
+        if let Some((pid, pkg, is_game)) = &self.top_app {
+            events.push(Event::TopAppChange(*pid, pkg.clone(), *is_game));
+        }
+
+        if let Some((pid, pkg)) = &self.game {
+            events.push(Event::LoadFas(*pid, pkg.clone()));
+        }
+
+        if self.started {
+            events.push(Event::StartFas);
+        }
+
+        if self.cpu_freq_inited {
+            events.push(Event::InitCpuFreq);
+        }
+
+        if let (Some(target_fps), Some((_, pkg))) = (self.target_fps, &self.game) {
+            events.push(Event::TargetFpsChange(target_fps, pkg.clone()));
+        }
+
+        events
+    }
+}
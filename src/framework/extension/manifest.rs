@@ -0,0 +1,255 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    cmp::Reverse,
+    path::{Path, PathBuf},
+};
+
+use log::{error, warn};
+use mlua::{Lua, Table};
+use serde::Deserialize;
+
+use super::core::ExtensionList;
+use crate::framework::error::Result;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Manifest {
+    pub name: String,
+    pub version: Option<String>,
+    pub api_version: Option<u8>,
+    pub priority: i32,
+    pub conflicts: Vec<String>,
+    pub after: Vec<String>,
+}
+
+pub fn sidecar(path: &Path) -> PathBuf {
+    path.with_extension("manifest.toml")
+}
+
+impl Manifest {
+    // A sidecar `<name>.manifest.toml` wins over the `MANIFEST` table of the script.
+    // Wasm extensions only have the sidecar. It is read by the caller, once, so that what
+    // `trust` verified is what gets used.
+    pub fn read(path: &Path, sidecar: Option<&str>, lua: Option<&Lua>) -> Result<Self> {
+        let mut manifest = if let Some(sidecar) = sidecar {
+            toml::from_str(sidecar)?
+        } else if let Some(lua) = lua
+            && let Some(table) = lua.globals().get::<Option<Table>>("MANIFEST")?
+        {
+            Self::from_table(&table)?
+        } else {
+            Self::default()
+        };
+
+        if manifest.name.is_empty() {
+            manifest.name = path
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned();
+        }
+
+        Ok(manifest)
+    }
+
+    fn from_table(table: &Table) -> mlua::Result<Self> {
+        Ok(Self {
+            name: table.get::<Option<String>>("name")?.unwrap_or_default(),
+            version: table.get("version")?,
+            api_version: table.get("api_version")?,
+            priority: table.get::<Option<i32>>("priority")?.unwrap_or_default(),
+            conflicts: table
+                .get::<Option<Vec<String>>>("conflicts")?
+                .unwrap_or_default(),
+            after: table
+                .get::<Option<Vec<String>>>("after")?
+                .unwrap_or_default(),
+        })
+    }
+}
+
+// Highest priority first, names break ties, `after` relations are honored on top of that.
+pub fn sort(mut extensions: ExtensionList) -> ExtensionList {
+    extensions.sort_by(|(_, a), (_, b)| {
+        b.manifest
+            .priority
+            .cmp(&a.manifest.priority)
+            .then_with(|| a.manifest.name.cmp(&b.manifest.name))
+    });
+
+    let mut sorted = Vec::with_capacity(extensions.len());
+    while !extensions.is_empty() {
+        let ready = extensions.iter().position(|(_, extension)| {
+            extension.manifest.after.iter().all(|dependency| {
+                !extensions
+                    .iter()
+                    .any(|(_, other)| &other.manifest.name == dependency)
+            })
+        });
+
+        let index = ready.unwrap_or_else(|| {
+            let names: Vec<_> = extensions
+                .iter()
+                .map(|(_, extension)| extension.manifest.name.as_str())
+                .collect();
+            warn!("Dependency cycle between extensions {names:?}, fall back to priority order");
+            0
+        });
+
+        sorted.push(extensions.remove(index));
+    }
+
+    sorted
+}
+
+// Of two conflicting extensions the one already running is kept, otherwise the one that sorts
+// first. Running ones are never evicted, they would miss their unload and stop callbacks.
+// Returns the dropped ones with the reason, to be listed as failed.
+pub fn drop_conflicts(
+    extensions: &mut ExtensionList,
+    is_new: impl Fn(&Path) -> bool,
+) -> Vec<(PathBuf, String)> {
+    // A stable sort keeps the call order within running and new ones
+    let mut order: Vec<_> = (0..extensions.len()).collect();
+    order.sort_by_key(|index| is_new(&extensions[*index].0));
+
+    let mut kept: Vec<usize> = Vec::new();
+    let mut dropped = Vec::new();
+    for index in order {
+        let manifest = &extensions[index].1.manifest;
+        let conflict = kept
+            .iter()
+            .map(|kept| &extensions[*kept].1.manifest)
+            .find(|other| {
+                manifest.conflicts.contains(&other.name) || other.conflicts.contains(&manifest.name)
+            });
+
+        if let Some(other) = conflict {
+            error!(
+                "Extension '{}' conflicts with '{}', it will not be loaded",
+                manifest.name, other.name
+            );
+            dropped.push((index, format!("Conflicts with extension '{}'", other.name)));
+        } else {
+            kept.push(index);
+        }
+    }
+
+    dropped.sort_by_key(|(index, _)| Reverse(*index));
+    dropped
+        .into_iter()
+        .map(|(index, reason)| (extensions.remove(index).0, reason))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::extension::core::{LoadedExtension, Runtime};
+
+    fn extension(
+        name: &str,
+        priority: i32,
+        after: &[&str],
+        conflicts: &[&str],
+    ) -> (PathBuf, LoadedExtension) {
+        let manifest = Manifest {
+            name: name.into(),
+            // Below v5, so that loading does not look for callbacks
+            api_version: Some(4),
+            priority,
+            after: after.iter().map(|name| (*name).into()).collect(),
+            conflicts: conflicts.iter().map(|name| (*name).into()).collect(),
+            ..Manifest::default()
+        };
+
+        (
+            PathBuf::from(format!("{name}.lua")),
+            LoadedExtension::new(Runtime::Lua(Lua::new()), manifest),
+        )
+    }
+
+    fn names(extensions: &ExtensionList) -> Vec<&str> {
+        extensions
+            .iter()
+            .map(|(_, extension)| extension.manifest.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn priority_then_name() {
+        let sorted = sort(vec![
+            extension("b", 0, &[], &[]),
+            extension("c", 10, &[], &[]),
+            extension("a", 0, &[], &[]),
+        ]);
+
+        assert_eq!(names(&sorted), ["c", "a", "b"]);
+    }
+
+    #[test]
+    fn after_wins_over_priority() {
+        let sorted = sort(vec![
+            extension("a", 10, &["b"], &[]),
+            extension("b", 0, &[], &[]),
+            extension("c", 5, &[], &[]),
+        ]);
+
+        assert_eq!(names(&sorted), ["c", "b", "a"]);
+    }
+
+    #[test]
+    fn cycle_falls_back_to_priority() {
+        let sorted = sort(vec![
+            extension("a", 0, &["b"], &[]),
+            extension("b", 10, &["a"], &[]),
+        ]);
+
+        assert_eq!(names(&sorted), ["b", "a"]);
+    }
+
+    #[test]
+    fn first_in_order_wins_conflict() {
+        let mut extensions = sort(vec![
+            extension("a", 10, &[], &["b"]),
+            extension("b", 0, &[], &[]),
+            extension("c", 0, &[], &[]),
+        ]);
+        let dropped = drop_conflicts(&mut extensions, |_| true);
+
+        assert_eq!(names(&extensions), ["a", "c"]);
+        assert_eq!(dropped.len(), 1);
+        assert_eq!(dropped[0].0, Path::new("b.lua"));
+        assert!(dropped[0].1.contains("'a'"));
+    }
+
+    #[test]
+    fn running_wins_conflict() {
+        let mut extensions = sort(vec![
+            extension("a", 10, &[], &["b"]),
+            extension("b", 0, &[], &[]),
+        ]);
+        let dropped = drop_conflicts(&mut extensions, |path| path == Path::new("a.lua"));
+
+        assert_eq!(names(&extensions), ["b"]);
+        assert_eq!(dropped.len(), 1);
+        assert_eq!(dropped[0].0, Path::new("a.lua"));
+        assert!(dropped[0].1.contains("'b'"));
+    }
+}
@@ -17,35 +17,176 @@
 
 pub mod api;
 mod core;
+mod harness;
+mod health;
+mod lifecycle;
+mod manifest;
+mod queue;
+mod sandbox;
+mod settings;
+mod storage;
+mod timer;
+mod trust;
+mod wasm;
 
 use std::{
+    cell::Cell,
     fs,
-    sync::mpsc::{self, SyncSender},
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::{self, Receiver, Sender},
+    },
     thread,
+    time::{Duration, Instant},
 };
 
+use toml::Table;
+
 use crate::framework::error::Result;
-pub use api::Api;
+pub use api::Event;
+use api::{
+    control::{ControlDecision, ControlInput, ControlRequest},
+    request::{Request, Requester},
+};
+pub use harness::run as ext_test;
+use queue::EventQueue;
+pub use sandbox::Sandbox;
+pub use trust::trust as ext_trust;
 
 const EXTENSIONS_PATH: &str = "/dev/fas_rs/extensions";
+// Fixed rather than read from games.toml, which any app with storage access can edit: whatever
+// lands in here runs as root.
+const PERSISTENT_PATH: &str = "/data/adb/fas-rs/extensions";
+// Past this the frame is gone anyway, fall back to the built-in controller
+const CONTROL_TIMEOUT: Duration = Duration::from_millis(8);
+
+// What the loaded extensions listen to, so the scheduler does not queue events nobody handles.
+#[derive(Debug)]
+pub struct Listeners {
+    loaded: AtomicBool,
+    frame_interval_ms: AtomicU64,
+    jank: AtomicBool,
+    control: AtomicBool,
+}
+
+impl Listeners {
+    const fn new() -> Self {
+        Self {
+            loaded: AtomicBool::new(false),
+            frame_interval_ms: AtomicU64::new(u64::MAX),
+            jank: AtomicBool::new(false),
+            control: AtomicBool::new(false),
+        }
+    }
+
+    pub fn set_loaded(&self, loaded: bool) {
+        self.loaded.store(loaded, Ordering::Release);
+    }
+
+    pub fn set_frame_interval(&self, interval: Option<Duration>) {
+        let interval = interval.map_or(u64::MAX, |interval| interval.as_millis() as u64);
+        self.frame_interval_ms.store(interval, Ordering::Release);
+    }
+
+    pub fn set_jank(&self, jank: bool) {
+        self.jank.store(jank, Ordering::Release);
+    }
+
+    pub fn set_control(&self, control: bool) {
+        self.control.store(control, Ordering::Release);
+    }
+}
 
 pub struct Extension {
-    sx: SyncSender<Box<dyn Api>>,
+    queue: Arc<EventQueue>,
+    listeners: Arc<Listeners>,
+    last_frame: Cell<Instant>,
+    requests: Receiver<(String, Request)>,
+    request_sx: Sender<(String, Request)>,
 }
 
 impl Extension {
-    pub fn init() -> Result<Self> {
-        let _ = fs::create_dir_all(EXTENSIONS_PATH);
-        let (sx, rx) = mpsc::sync_channel(16);
+    // Scripts in `PERSISTENT_PATH` win over the tmpfs ones with the same file name.
+    pub fn init(sandbox: Sandbox, settings: Table) -> Result<Self> {
+        let dirs = vec![
+            PathBuf::from(PERSISTENT_PATH),
+            PathBuf::from(EXTENSIONS_PATH),
+        ];
+        for dir in &dirs {
+            let _ = fs::create_dir_all(dir);
+        }
+
+        let queue = Arc::new(EventQueue::new());
+        let listeners = Arc::new(Listeners::new());
+        let (request_sx, requests) = mpsc::channel();
 
-        thread::Builder::new()
-            .name("ExtensionThread".into())
-            .spawn(move || core::thread(&rx))?;
+        {
+            let queue = queue.clone();
+            let listeners = listeners.clone();
+            let request_sx = request_sx.clone();
+            thread::Builder::new()
+                .name("ExtensionThread".into())
+                .spawn(move || {
+                    core::thread(&queue, &listeners, &request_sx, &sandbox, &dirs, settings);
+                })?;
+        }
+
+        Ok(Self {
+            queue,
+            listeners,
+            last_frame: Cell::new(Instant::now()),
+            requests,
+            request_sx,
+        })
+    }
+
+    // For requests that do not come from an extension, they show up in the log under `name`.
+    pub fn requester(&self, name: &str) -> Requester {
+        Requester::new(name.into(), self.request_sx.clone())
+    }
+
+    pub fn requests(&self) -> impl Iterator<Item = (String, Request)> + '_ {
+        self.requests.try_iter()
+    }
+
+    pub fn any_loaded(&self) -> bool {
+        self.listeners.loaded.load(Ordering::Acquire)
+    }
+
+    pub fn frame_due(&self) -> bool {
+        let interval = self.listeners.frame_interval_ms.load(Ordering::Acquire);
+        if interval == u64::MAX || self.last_frame.get().elapsed() < Duration::from_millis(interval)
+        {
+            return false;
+        }
+
+        self.last_frame.set(Instant::now());
+        true
+    }
+
+    pub fn jank_listened(&self) -> bool {
+        self.listeners.jank.load(Ordering::Acquire)
+    }
+
+    // Asks the `compute_control` extension, `None` means use the built-in controller.
+    pub fn compute_control(&self, input: ControlInput) -> Option<ControlDecision> {
+        if !self.listeners.control.load(Ordering::Acquire) {
+            return None;
+        }
+
+        let (reply, rx) = mpsc::channel();
+        self.queue.request_control(ControlRequest { input, reply });
+        rx.recv_timeout(CONTROL_TIMEOUT).ok().flatten()
+    }
 
-        Ok(Self { sx })
+    // The whole `[extensions]` section, every extension gets its own part of it.
+    pub fn update_settings(&self, settings: Table) {
+        self.queue.update_settings(settings);
     }
 
-    pub fn trigger_extentions(&self, trigger: impl Api + 'static) {
-        let _ = self.sx.try_send(trigger.into_box());
+    pub fn trigger_extentions(&self, event: Event) {
+        self.queue.push(event);
     }
 }
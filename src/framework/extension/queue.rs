@@ -0,0 +1,196 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    collections::VecDeque,
+    mem,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use parking_lot::{Condvar, Mutex};
+use toml::Table;
+
+use super::api::{Event, control::ControlRequest, event::EventKind};
+
+const SAMPLE_CAPACITY: usize = 16;
+
+pub enum Task {
+    Event(Event),
+    Control(ControlRequest),
+    Settings(Table),
+}
+
+#[derive(Debug)]
+struct Pending {
+    events: VecDeque<Event>,
+    // Only the latest one is worth answering, the looper gave up on older ones
+    control: Option<ControlRequest>,
+    settings: Option<Table>,
+}
+
+#[derive(Debug)]
+pub struct EventQueue {
+    pending: Mutex<Pending>,
+    ready: Condvar,
+    dropped: AtomicU64,
+}
+
+impl EventQueue {
+    pub const fn new() -> Self {
+        Self {
+            pending: Mutex::new(Pending {
+                events: VecDeque::new(),
+                control: None,
+                settings: None,
+            }),
+            ready: Condvar::new(),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    pub fn push(&self, event: Event) {
+        let mut pending = self.pending.lock();
+        let events = &mut pending.events;
+
+        match event.kind() {
+            EventKind::Lifecycle => events.push_back(event),
+            EventKind::State => {
+                // Only coalesce after the last lifecycle event, so it never jumps across one
+                let barrier = events
+                    .iter()
+                    .rposition(|queued| queued.kind() == EventKind::Lifecycle)
+                    .map_or(0, |index| index + 1);
+
+                if let Some(queued) = events
+                    .range_mut(barrier..)
+                    .find(|queued| mem::discriminant(&**queued) == mem::discriminant(&event))
+                {
+                    *queued = event;
+                } else {
+                    events.push_back(event);
+                }
+            }
+            EventKind::Sample => {
+                let samples = events
+                    .iter()
+                    .filter(|queued| queued.kind() == EventKind::Sample)
+                    .count();
+
+                if samples >= SAMPLE_CAPACITY {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+
+                events.push_back(event);
+            }
+        }
+
+        drop(pending);
+        self.ready.notify_one();
+    }
+
+    pub fn request_control(&self, request: ControlRequest) {
+        self.pending.lock().control = Some(request);
+        self.ready.notify_one();
+    }
+
+    pub fn update_settings(&self, settings: Table) {
+        self.pending.lock().settings = Some(settings);
+        self.ready.notify_one();
+    }
+
+    // Control requests go first, the looper is waiting on them.
+    pub fn pop_timeout(&self, timeout: Duration) -> Option<Task> {
+        let mut pending = self.pending.lock();
+        if pending.events.is_empty() && pending.control.is_none() && pending.settings.is_none() {
+            self.ready.wait_for(&mut pending, timeout);
+        }
+
+        if let Some(request) = pending.control.take() {
+            return Some(Task::Control(request));
+        }
+
+        if let Some(settings) = pending.settings.take() {
+            return Some(Task::Settings(settings));
+        }
+
+        pending.events.pop_front().map(Task::Event)
+    }
+
+    // Sample events dropped since the last call.
+    pub fn take_dropped(&self) -> u64 {
+        self.dropped.swap(0, Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter;
+
+    use super::*;
+
+    fn drain(queue: &EventQueue) -> Vec<Event> {
+        iter::from_fn(|| match queue.pop_timeout(Duration::ZERO) {
+            Some(Task::Event(event)) => Some(event),
+            _ => None,
+        })
+        .collect()
+    }
+
+    #[test]
+    fn lifecycle_kept() {
+        let queue = EventQueue::new();
+        for _ in 0..SAMPLE_CAPACITY * 4 {
+            queue.push(Event::StartFas);
+            queue.push(Event::StopFas);
+        }
+
+        assert_eq!(drain(&queue).len(), SAMPLE_CAPACITY * 8);
+        assert_eq!(queue.take_dropped(), 0);
+    }
+
+    #[test]
+    fn state_coalesced_after_lifecycle() {
+        let queue = EventQueue::new();
+        queue.push(Event::TargetFpsChange(60, "a".into()));
+        queue.push(Event::StartFas);
+        queue.push(Event::TargetFpsChange(90, "b".into()));
+        queue.push(Event::TargetFpsChange(120, "c".into()));
+
+        let events = drain(&queue);
+        assert_eq!(events.len(), 3);
+        assert!(matches!(&events[0], Event::TargetFpsChange(60, pkg) if pkg == "a"));
+        assert!(matches!(events[1], Event::StartFas));
+        assert!(matches!(&events[2], Event::TargetFpsChange(120, pkg) if pkg == "c"));
+    }
+
+    #[test]
+    fn samples_capped() {
+        let queue = EventQueue::new();
+        for _ in 0..SAMPLE_CAPACITY + 3 {
+            queue.push(Event::Jank(0, 60));
+        }
+        queue.push(Event::StopFas);
+
+        let events = drain(&queue);
+        assert_eq!(events.len(), SAMPLE_CAPACITY + 1);
+        assert!(matches!(events.last(), Some(Event::StopFas)));
+        assert_eq!(queue.take_dropped(), 3);
+        assert_eq!(queue.take_dropped(), 0);
+    }
+}
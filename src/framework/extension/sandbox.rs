@@ -0,0 +1,157 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    cell::Cell,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+#[cfg(debug_assertions)]
+use log::debug;
+use log::{error, warn};
+use mlua::{HookTriggers, Lua, Table, Value, VmState};
+
+use super::trust;
+use crate::framework::error::Result;
+
+// Like the trusted-only mode, a root-owned file rather than a games.toml key: any app with
+// storage access can edit games.toml.
+const ALLOW_UNSAFE_PATH: &str = "/data/adb/fas-rs/allow_unsafe_extensions";
+
+// How often the budget hook runs, in vm instructions.
+const HOOK_INSTRUCTIONS: u32 = 10_000;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Sandbox {
+    pub allow_unsafe: bool,
+    pub memory_limit: usize,
+    pub callback_timeout: Duration,
+    pub max_failures: u32,
+}
+
+struct Budget {
+    timeout: Duration,
+    memory_limit: Option<usize>,
+    deadline: Cell<Option<Instant>>,
+}
+
+impl Sandbox {
+    pub fn unsafe_allowed() -> bool {
+        let path = Path::new(ALLOW_UNSAFE_PATH);
+        if !path.exists() {
+            return false;
+        }
+
+        match trust::check_root_only(path) {
+            Ok(()) => {
+                warn!("{ALLOW_UNSAFE_PATH} exists, extensions may use os.execute and io.popen");
+                true
+            }
+            Err(e) => {
+                error!("Ignoring {ALLOW_UNSAFE_PATH}: {e}");
+                false
+            }
+        }
+    }
+
+    pub fn create_lua(&self) -> Result<Lua> {
+        let lua = Lua::new();
+
+        // Exiting from an extension would take the whole daemon down
+        let os: Table = lua.globals().get("os")?;
+        os.set("exit", Value::Nil)?;
+
+        if !self.allow_unsafe {
+            os.set("execute", Value::Nil)?;
+
+            let io: Table = lua.globals().get("io")?;
+            io.set("popen", Value::Nil)?;
+
+            let package: Table = lua.globals().get("package")?;
+            package.set("loadlib", Value::Nil)?;
+            package.set("cpath", "")?;
+        }
+
+        // Count hooks do not fire inside jit compiled traces, and an extension must neither turn
+        // the jit back on nor replace the hook that enforces its budget.
+        lua.load(
+            r"
+            if jit then jit.off() end
+            jit = nil
+            package.loaded.jit = nil
+            if debug then debug.sethook = nil end
+            ",
+        )
+        .exec()?;
+
+        // LuaJIT has no custom allocator, in that case the hook checks the gc count instead
+        let memory_limit = if lua.set_memory_limit(self.memory_limit).is_ok() {
+            None
+        } else {
+            #[cfg(debug_assertions)]
+            debug!("Memory limit is not available, fall back to gc count check");
+            Some(self.memory_limit)
+        };
+
+        lua.set_app_data(Budget {
+            timeout: self.callback_timeout,
+            memory_limit,
+            deadline: Cell::new(None),
+        });
+        lua.set_hook(
+            HookTriggers::new().every_nth_instruction(HOOK_INSTRUCTIONS),
+            |lua, _| {
+                if let Some(budget) = lua.app_data_ref::<Budget>() {
+                    if budget
+                        .deadline
+                        .get()
+                        .is_some_and(|deadline| Instant::now() >= deadline)
+                    {
+                        return Err(mlua::Error::runtime("Callback exceeded its time budget"));
+                    }
+
+                    if budget
+                        .memory_limit
+                        .is_some_and(|limit| lua.used_memory() > limit)
+                    {
+                        return Err(mlua::Error::runtime("Extension exceeded its memory limit"));
+                    }
+                }
+
+                Ok(VmState::Continue)
+            },
+        );
+
+        Ok(lua)
+    }
+}
+
+// Runs `f` under the callback time budget of this lua state.
+pub fn guard<R>(lua: &Lua, f: impl FnOnce() -> mlua::Result<R>) -> mlua::Result<R> {
+    if let Some(budget) = lua.app_data_ref::<Budget>() {
+        budget.deadline.set(Some(Instant::now() + budget.timeout));
+    }
+
+    let result = f();
+
+    if let Some(budget) = lua.app_data_ref::<Budget>() {
+        budget.deadline.set(None);
+    }
+
+    result
+}
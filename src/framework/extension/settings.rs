@@ -0,0 +1,88 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::path::Path;
+
+use mlua::{Lua, Value};
+use toml::Table;
+
+use super::{
+    api::misc::do_callback,
+    core::{LoadedExtension, Runtime},
+    wasm::WasmArg,
+};
+
+// `[extensions.<name>]` of games.toml, `<name>` being the file name of the extension without
+// its extension, the same one its storage uses.
+pub fn section(settings: &Table, path: &Path) -> Table {
+    path.file_stem()
+        .and_then(|stem| settings.get(stem.to_string_lossy().as_ref()))
+        .and_then(toml::Value::as_table)
+        .cloned()
+        .unwrap_or_default()
+}
+
+pub fn to_lua_table(lua: &Lua, table: &Table) -> mlua::Result<mlua::Table> {
+    let lua_table = lua.create_table()?;
+    for (key, value) in table {
+        lua_table.set(key.as_str(), to_lua(lua, value)?)?;
+    }
+
+    Ok(lua_table)
+}
+
+fn to_lua(lua: &Lua, value: &toml::Value) -> mlua::Result<Value> {
+    Ok(match value {
+        toml::Value::String(s) => Value::String(lua.create_string(s)?),
+        toml::Value::Integer(i) => Value::Integer(*i),
+        toml::Value::Float(f) => Value::Number(*f),
+        toml::Value::Boolean(b) => Value::Boolean(*b),
+        toml::Value::Datetime(datetime) => Value::String(lua.create_string(datetime.to_string())?),
+        toml::Value::Array(array) => {
+            let lua_table = lua.create_table()?;
+            for value in array {
+                lua_table.push(to_lua(lua, value)?)?;
+            }
+
+            Value::Table(lua_table)
+        }
+        toml::Value::Table(table) => Value::Table(to_lua_table(lua, table)?),
+    })
+}
+
+// Lua gets a table, wasm the section as toml text.
+pub fn notify(path: &Path, extension: &LoadedExtension, section: &Table) {
+    if extension.api_version < 5 {
+        return;
+    }
+
+    match &extension.runtime {
+        Runtime::Lua(lua) => {
+            let Ok(table) = to_lua_table(lua, section) else {
+                return;
+            };
+
+            let _ = lua.globals().set("EXTENSION_CONFIG", &table);
+            do_callback(path, lua, "config_change", table);
+        }
+        Runtime::Wasm(wasm) => {
+            if let Ok(raw) = toml::to_string(section) {
+                wasm.call(path, "config_change", &[WasmArg::Str(raw)]);
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{cell::RefCell, fs, path::PathBuf};
+
+use anyhow::bail;
+use mlua::{Lua, Value};
+use toml::Table;
+
+use super::harness;
+
+const STORAGE_PATH: &str = "/data/adb/fas-rs/storage";
+const MAX_SIZE: usize = 64 * 1024;
+
+// Per extension key-value store, survives reboots unlike the extensions directory.
+pub struct Storage {
+    // `None` keeps it in memory only, for `ext-test`
+    path: Option<PathBuf>,
+    table: RefCell<Table>,
+}
+
+impl Storage {
+    pub fn open(extension: &str, persistent: bool) -> Self {
+        let path =
+            persistent.then(|| PathBuf::from(STORAGE_PATH).join(format!("{extension}.toml")));
+        let table = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|raw| toml::from_str(&raw).ok())
+            .unwrap_or_default();
+
+        Self {
+            path,
+            table: RefCell::new(table),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<toml::Value> {
+        self.table.borrow().get(key).cloned()
+    }
+
+    // `None` removes the key.
+    pub fn set(&self, key: String, value: Option<toml::Value>) -> anyhow::Result<()> {
+        let mut table = self.table.borrow().clone();
+        match value {
+            Some(value) => table.insert(key, value),
+            None => table.remove(&key),
+        };
+
+        let raw = toml::to_string(&table)?;
+        if raw.len() > MAX_SIZE {
+            bail!("Storage is limited to {MAX_SIZE} bytes per extension");
+        }
+
+        if let Some(path) = &self.path {
+            fs::create_dir_all(STORAGE_PATH)?;
+            fs::write(path, raw)?;
+        }
+        *self.table.borrow_mut() = table;
+
+        Ok(())
+    }
+}
+
+pub fn init(lua: &Lua, extension: &str) {
+    lua.set_app_data(Storage::open(extension, !harness::is_recording(lua)));
+}
+
+pub fn get(lua: &Lua, key: &str) -> mlua::Result<Value> {
+    let storage = lua
+        .app_data_ref::<Storage>()
+        .ok_or_else(|| mlua::Error::runtime("Storage is not available"))?;
+
+    Ok(match storage.get(key) {
+        Some(toml::Value::Boolean(b)) => Value::Boolean(b),
+        Some(toml::Value::Integer(i)) => Value::Integer(i),
+        Some(toml::Value::Float(f)) => Value::Number(f),
+        Some(toml::Value::String(s)) => Value::String(lua.create_string(s)?),
+        _ => Value::Nil,
+    })
+}
+
+pub fn set(lua: &Lua, key: String, value: Value) -> mlua::Result<()> {
+    let storage = lua
+        .app_data_ref::<Storage>()
+        .ok_or_else(|| mlua::Error::runtime("Storage is not available"))?;
+    let value = match value {
+        Value::Nil => None,
+        Value::Boolean(b) => Some(toml::Value::Boolean(b)),
+        Value::Integer(i) => Some(toml::Value::Integer(i)),
+        Value::Number(f) => Some(toml::Value::Float(f)),
+        Value::String(s) => Some(toml::Value::String(s.to_str()?.to_string())),
+        _ => {
+            return Err(mlua::Error::runtime(
+                "Only booleans, numbers and strings can be stored",
+            ));
+        }
+    };
+
+    storage.set(key, value).map_err(mlua::Error::external)
+}
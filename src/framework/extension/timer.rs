@@ -0,0 +1,149 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    cell::{Cell, RefCell},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use mlua::{Function, Lua, Table, Value};
+
+use super::health;
+
+// Callbacks live in the lua registry, so the rust side only keeps the schedule.
+const TIMERS_KEY: &str = "fas_rs_timers";
+const MIN_INTERVAL: Duration = Duration::from_millis(10);
+
+struct Timer {
+    id: u64,
+    due: Instant,
+    interval: Option<Duration>,
+}
+
+// The schedule alone, wasm extensions keep one next to their store.
+#[derive(Default)]
+pub struct Timers {
+    next_id: Cell<u64>,
+    timers: RefCell<Vec<Timer>>,
+}
+
+impl Timers {
+    pub fn add(&self, delay: Duration, repeat: bool) -> u64 {
+        let delay = delay.max(MIN_INTERVAL);
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+
+        self.timers.borrow_mut().push(Timer {
+            id,
+            due: Instant::now() + delay,
+            interval: repeat.then_some(delay),
+        });
+
+        id
+    }
+
+    pub fn clear(&self, id: u64) {
+        self.timers.borrow_mut().retain(|timer| timer.id != id);
+    }
+
+    pub fn next_due(&self) -> Option<Instant> {
+        self.timers.borrow().iter().map(|timer| timer.due).min()
+    }
+
+    // Reschedules the repeating ones, returns the ids that fired and whether they repeat.
+    pub fn take_due(&self) -> Vec<(u64, bool)> {
+        let now = Instant::now();
+        let mut fired = Vec::new();
+
+        self.timers.borrow_mut().retain_mut(|timer| {
+            if timer.due > now {
+                return true;
+            }
+
+            fired.push((timer.id, timer.interval.is_some()));
+            timer.interval.is_some_and(|interval| {
+                timer.due = now + interval;
+                true
+            })
+        });
+
+        fired
+    }
+}
+
+pub fn init(lua: &Lua) -> mlua::Result<()> {
+    lua.set_app_data(Timers::default());
+    lua.set_named_registry_value(TIMERS_KEY, lua.create_table()?)
+}
+
+pub fn set_interval(lua: &Lua, ms: u64, callback: Function) -> mlua::Result<u64> {
+    add_timer(lua, Duration::from_millis(ms), callback, true)
+}
+
+pub fn set_timeout(lua: &Lua, ms: u64, callback: Function) -> mlua::Result<u64> {
+    add_timer(lua, Duration::from_millis(ms), callback, false)
+}
+
+pub fn clear_timer(lua: &Lua, id: u64) -> mlua::Result<()> {
+    if let Some(timers) = lua.app_data_ref::<Timers>() {
+        timers.clear(id);
+    }
+
+    lua.named_registry_value::<Table>(TIMERS_KEY)?
+        .set(id, Value::Nil)
+}
+
+fn add_timer(lua: &Lua, delay: Duration, callback: Function, repeat: bool) -> mlua::Result<u64> {
+    let timers = lua
+        .app_data_ref::<Timers>()
+        .ok_or_else(|| mlua::Error::runtime("Timers are not available"))?;
+    let id = timers.add(delay, repeat);
+
+    lua.named_registry_value::<Table>(TIMERS_KEY)?
+        .set(id, callback)?;
+
+    Ok(id)
+}
+
+pub fn next_due(lua: &Lua) -> Option<Instant> {
+    lua.app_data_ref::<Timers>()?.next_due()
+}
+
+pub fn run_due<P: AsRef<Path>>(extension: P, lua: &Lua) {
+    let fired = lua
+        .app_data_ref::<Timers>()
+        .map(|timers| timers.take_due())
+        .unwrap_or_default();
+
+    let Ok(callbacks) = lua.named_registry_value::<Table>(TIMERS_KEY) else {
+        return;
+    };
+
+    for (id, repeat) in fired {
+        // Might have been cleared by a timer that fired just before
+        let Ok(Some(callback)) = callbacks.get::<Option<Function>>(id) else {
+            continue;
+        };
+
+        if !repeat {
+            let _ = callbacks.set(id, Value::Nil);
+        }
+
+        health::call(&extension, lua, || callback.call::<()>(()));
+    }
+}
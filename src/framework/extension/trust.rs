@@ -0,0 +1,139 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    collections::HashSet,
+    fmt::Write as _,
+    fs::{self, OpenOptions},
+    io::Write as _,
+    os::unix::fs::{MetadataExt, OpenOptionsExt},
+    path::Path,
+};
+
+use anyhow::{Context, anyhow};
+use log::error;
+use sha2::{Digest, Sha256};
+
+use super::manifest;
+use crate::framework::error::Result;
+
+// One `<sha256> <file name>` per line, the name is only there for humans.
+//
+// Its existence is what turns the trusted-only mode on: it lives where only root can write,
+// unlike games.toml which any app with storage access can edit.
+const TRUST_PATH: &str = "/data/adb/fas-rs/trusted_extensions";
+
+pub fn enforced() -> bool {
+    Path::new(TRUST_PATH).exists()
+}
+
+// Watched through its directory, the file itself may come and go.
+pub fn allowlist() -> &'static Path {
+    Path::new(TRUST_PATH)
+}
+
+fn digest(code: &[u8]) -> String {
+    Sha256::digest(code)
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
+
+// Switches and lists that decide what runs as root must only be changeable by root.
+pub fn check_root_only(path: &Path) -> Result<()> {
+    let metadata = fs::metadata(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    if metadata.uid() != 0 || metadata.mode() & 0o022 != 0 {
+        return Err(anyhow!(
+            "{} must be owned by root and not writable by others",
+            path.display()
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+// Anyone who can edit the allowlist could as well edit the extensions, so it has to be root's.
+fn read_allowlist() -> Result<HashSet<String>> {
+    check_root_only(Path::new(TRUST_PATH))?;
+
+    Ok(fs::read_to_string(TRUST_PATH)?
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(str::to_ascii_lowercase)
+        .collect())
+}
+
+// `code` is what is about to run, so the file cannot be swapped between checking and loading.
+pub fn verify(path: &Path, code: &[u8]) -> Result<()> {
+    let digest = digest(code);
+    let allowlist = read_allowlist().inspect_err(|e| {
+        error!("Refused to load extension {}: {e}", path.display());
+    })?;
+
+    if !allowlist.contains(&digest) {
+        error!(
+            "Refused to load untrusted extension {} (sha256 {digest}), run `fas-rs ext-trust {}` to allow it",
+            path.display(),
+            path.display()
+        );
+        return Err(anyhow!("sha256 {digest} is not in {TRUST_PATH}").into());
+    }
+
+    Ok(())
+}
+
+// `fas-rs ext-trust <file>`, the sidecar manifest of the extension is trusted along with it.
+pub fn trust<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    trust_file(path)?;
+
+    let sidecar = manifest::sidecar(path);
+    if sidecar.exists() {
+        trust_file(&sidecar)?;
+    }
+
+    Ok(())
+}
+
+fn trust_file(path: &Path) -> Result<()> {
+    let digest = digest(&fs::read(path)?);
+
+    if fs::read_to_string(TRUST_PATH)
+        .is_ok_and(|raw| raw.lines().any(|line| line.starts_with(&digest)))
+    {
+        println!("{} is already trusted ({digest})", path.display());
+        return Ok(());
+    }
+
+    if let Some(dir) = Path::new(TRUST_PATH).parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .mode(0o600)
+        .open(TRUST_PATH)?
+        .write_all(format!("{digest} {name}\n").as_bytes())?;
+
+    println!("Trusted {} ({digest})", path.display());
+    Ok(())
+}
@@ -0,0 +1,585 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    cell::RefCell,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use log::{debug, error, info};
+use serde::Serialize;
+use wasmi::{
+    Caller, Config, Engine, Error, Extern, Instance, Linker, Memory, Module, Store, StoreLimits,
+    StoreLimitsBuilder, Val,
+};
+
+use super::{
+    api::{
+        control::{ControlDecision, ControlInput},
+        helper_funs,
+        request::Requester,
+        snapshot::PolicySnapshot,
+    },
+    health::{self, Health},
+    sandbox::Sandbox,
+    storage::Storage,
+    timer::Timers,
+};
+use crate::framework::error::Result;
+
+// Host functions live in this import module, with the same names as the lua api.
+const MODULE: &str = "fas_rs";
+// A conservative guess of what wasmi gets through in a millisecond on a phone
+const FUEL_PER_MS: u64 = 100_000;
+
+type WasmResult<T> = std::result::Result<T, Error>;
+
+struct Host {
+    requester: Requester,
+    limits: StoreLimits,
+    timers: Timers,
+    storage: Storage,
+    // Filled by `decide_control`/`decide_freq` while `compute_control` runs
+    decision: Option<ControlDecision>,
+}
+
+// An extension compiled to wasm, isolated by its own linear memory and metered by fuel.
+pub struct WasmExtension {
+    store: RefCell<Store<Host>>,
+    instance: Instance,
+    fuel: u64,
+    health: Health,
+}
+
+// Callback arguments, strings are copied into memory the module hands out with `fas_alloc`.
+#[derive(Debug, Clone)]
+pub enum WasmArg {
+    I32(i32),
+    I64(i64),
+    F64(f64),
+    Str(String),
+}
+
+impl From<i32> for WasmArg {
+    fn from(value: i32) -> Self {
+        Self::I32(value)
+    }
+}
+
+impl From<u32> for WasmArg {
+    fn from(value: u32) -> Self {
+        Self::I32(value as i32)
+    }
+}
+
+impl From<bool> for WasmArg {
+    fn from(value: bool) -> Self {
+        Self::I32(i32::from(value))
+    }
+}
+
+impl From<u64> for WasmArg {
+    fn from(value: u64) -> Self {
+        Self::I64(value as i64)
+    }
+}
+
+impl From<f64> for WasmArg {
+    fn from(value: f64) -> Self {
+        Self::F64(value)
+    }
+}
+
+impl From<String> for WasmArg {
+    fn from(value: String) -> Self {
+        Self::Str(value)
+    }
+}
+
+impl WasmArg {
+    fn lower(
+        &self,
+        store: &mut Store<Host>,
+        instance: Instance,
+        params: &mut Vec<Val>,
+    ) -> WasmResult<()> {
+        match self {
+            Self::I32(value) => params.push(Val::I32(*value)),
+            Self::I64(value) => params.push(Val::I64(*value)),
+            Self::F64(value) => params.push(Val::F64((*value).into())),
+            Self::Str(value) => {
+                let len = i32::try_from(value.len()).map_err(|e| Error::new(e.to_string()))?;
+                let ptr = instance
+                    .get_typed_func::<i32, i32>(&*store, "fas_alloc")?
+                    .call(&mut *store, len)?;
+                instance
+                    .get_memory(&*store, "memory")
+                    .ok_or_else(|| Error::new("The module does not export its memory"))?
+                    .write(&mut *store, ptr as usize, value.as_bytes())
+                    .map_err(|e| Error::new(e.to_string()))?;
+
+                params.push(Val::I32(ptr));
+                params.push(Val::I32(len));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl WasmExtension {
+    pub fn new(path: &Path, code: &[u8], requester: Requester, sandbox: &Sandbox) -> Result<Self> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, code)?;
+
+        let mut store = Store::new(
+            &engine,
+            Host {
+                requester,
+                limits: StoreLimitsBuilder::new()
+                    .memory_size(sandbox.memory_limit)
+                    .build(),
+                timers: Timers::default(),
+                storage: Storage::open(
+                    &path.file_stem().unwrap_or_default().to_string_lossy(),
+                    true,
+                ),
+                decision: None,
+            },
+        );
+        store.limiter(|host| &mut host.limits);
+
+        let mut linker = Linker::new(&engine);
+        register_base(&mut linker)?;
+        register_queries(&mut linker)?;
+        register_controls(&mut linker)?;
+        register_runtime(&mut linker)?;
+
+        let fuel = sandbox.callback_timeout.as_millis() as u64 * FUEL_PER_MS;
+        store.set_fuel(fuel)?;
+        let instance = linker.instantiate(&mut store, &module)?.start(&mut store)?;
+
+        Ok(Self {
+            store: RefCell::new(store),
+            instance,
+            fuel,
+            health: Health::new(sandbox.max_failures),
+        })
+    }
+
+    pub const fn health(&self) -> &Health {
+        &self.health
+    }
+
+    pub fn has_callback(&self, name: &str) -> bool {
+        self.instance
+            .get_func(&*self.store.borrow(), name)
+            .is_some()
+    }
+
+    // Exported as a global, like `API_VERSION` in lua.
+    pub fn api_version(&self) -> u8 {
+        self.global("API_VERSION")
+            .map_or(0, |version| version as u8)
+    }
+
+    pub fn frame_interval(&self) -> Option<Duration> {
+        self.global("ON_FRAME_INTERVAL")
+            .map(|interval| Duration::from_millis(interval as u64))
+    }
+
+    fn global(&self, name: &str) -> Option<i64> {
+        let store = self.store.borrow();
+        let value = self.instance.get_global(&*store, name)?.get(&*store);
+        value.i32().map(i64::from).or_else(|| value.i64())
+    }
+
+    // `None` if the module does not export `name` or the call failed.
+    pub fn call(&self, extension: &Path, name: &str, args: &[WasmArg]) -> Option<()> {
+        let mut store = self.store.borrow_mut();
+        let func = self.instance.get_func(&*store, name)?;
+
+        health::track(
+            extension,
+            || Some(&self.health),
+            || -> WasmResult<()> {
+                store.set_fuel(self.fuel)?;
+
+                let mut params = Vec::with_capacity(args.len());
+                for arg in args {
+                    arg.lower(&mut store, self.instance, &mut params)?;
+                }
+
+                func.call(&mut *store, &params, &mut [])
+            },
+        )
+    }
+
+    pub fn next_timer(&self) -> Option<Instant> {
+        self.store.borrow().data().timers.next_due()
+    }
+
+    // Timers fire `on_timer(id)`, there are no callbacks to hand over.
+    pub fn run_timers(&self, extension: &Path) {
+        let fired = self.store.borrow().data().timers.take_due();
+        for (id, _) in fired {
+            self.call(extension, "on_timer", &[WasmArg::from(id)]);
+        }
+    }
+
+    // The state comes as toml text, the module answers through `decide_control`/`decide_freq`.
+    // Deciding nothing, or failing halfway, falls back to the built-in controller.
+    pub fn compute_control(
+        &self,
+        extension: &Path,
+        input: &ControlInput,
+    ) -> Option<ControlDecision> {
+        let state = input
+            .to_toml()
+            .map_err(|e| error!("Failed to serialize the control state: {e}"))
+            .ok()?;
+
+        self.store.borrow_mut().data_mut().decision = None;
+        self.call(extension, "compute_control", &[WasmArg::Str(state)])?;
+        self.store.borrow_mut().data_mut().decision.take()
+    }
+}
+
+fn memory(caller: &Caller<'_, Host>) -> WasmResult<Memory> {
+    caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| Error::new("The module does not export its memory"))
+}
+
+// Checked against the module's memory before anything is copied, so the length it passes
+// cannot make the daemon allocate past `extension_memory_limit`.
+fn read_str(caller: &Caller<'_, Host>, ptr: i32, len: i32) -> WasmResult<String> {
+    let out_of_bounds = || Error::new(format!("String at {ptr} of {len} bytes is out of bounds"));
+    let start = usize::try_from(ptr).map_err(|_| out_of_bounds())?;
+    let end = usize::try_from(len)
+        .ok()
+        .and_then(|len| start.checked_add(len))
+        .ok_or_else(out_of_bounds)?;
+
+    let bytes = memory(caller)?
+        .data(caller)
+        .get(start..end)
+        .ok_or_else(out_of_bounds)?;
+    std::str::from_utf8(bytes)
+        .map(ToOwned::to_owned)
+        .map_err(|e| Error::new(e.to_string()))
+}
+
+// Copies as much as fits, returns the full length so the module can retry with a bigger buffer.
+fn write_str(caller: &mut Caller<'_, Host>, ptr: i32, len: i32, value: &str) -> WasmResult<i32> {
+    let bytes = value.as_bytes();
+    let copied = bytes.len().min(len.max(0) as usize);
+    memory(caller)?
+        .write(&mut *caller, ptr as usize, &bytes[..copied])
+        .map_err(|e| Error::new(e.to_string()))?;
+    Ok(bytes.len() as i32)
+}
+
+fn write_toml<T: Serialize>(
+    caller: &mut Caller<'_, Host>,
+    ptr: i32,
+    len: i32,
+    value: &T,
+) -> WasmResult<i32> {
+    let raw = toml::to_string(value).map_err(|e| Error::new(e.to_string()))?;
+    write_str(caller, ptr, len, &raw)
+}
+
+// Negative numbers stand for lua's nil.
+fn optional(value: i64) -> Option<isize> {
+    (value >= 0).then_some(value as isize)
+}
+
+fn to_wasm_error(e: &mlua::Error) -> Error {
+    Error::new(e.to_string())
+}
+
+fn register_base(linker: &mut Linker<Host>) -> WasmResult<()> {
+    linker.func_wrap(
+        MODULE,
+        "log_info",
+        |caller: Caller<'_, Host>, ptr: i32, len: i32| -> WasmResult<()> {
+            info!("extension: {}", read_str(&caller, ptr, len)?);
+            Ok(())
+        },
+    )?;
+
+    linker.func_wrap(
+        MODULE,
+        "log_debug",
+        |caller: Caller<'_, Host>, ptr: i32, len: i32| -> WasmResult<()> {
+            debug!("extension: {}", read_str(&caller, ptr, len)?);
+            Ok(())
+        },
+    )?;
+
+    linker.func_wrap(
+        MODULE,
+        "log_error",
+        |caller: Caller<'_, Host>, ptr: i32, len: i32| -> WasmResult<()> {
+            error!("extension: {}", read_str(&caller, ptr, len)?);
+            Ok(())
+        },
+    )?;
+
+    linker.func_wrap(MODULE, "set_ignore_policy", |policy: i32, val: i32| {
+        helper_funs::set_ignore_policy(policy, val != 0);
+    })?;
+
+    linker.func_wrap(
+        MODULE,
+        "set_extra_policy_abs",
+        |policy: i32, min: i64, max: i64| {
+            helper_funs::set_extra_policy_abs(policy, optional(min), optional(max));
+        },
+    )?;
+
+    linker.func_wrap(
+        MODULE,
+        "set_extra_policy_rel",
+        |policy: i32, target_policy: i32, min: i64, max: i64| {
+            helper_funs::set_extra_policy_rel(policy, target_policy, optional(min), optional(max));
+        },
+    )?;
+
+    linker.func_wrap(MODULE, "remove_extra_policy", |policy: i32| {
+        helper_funs::remove_extra_policy(policy);
+    })?;
+
+    Ok(())
+}
+
+fn register_queries(linker: &mut Linker<Host>) -> WasmResult<()> {
+    linker.func_wrap(
+        MODULE,
+        "get_mode",
+        |mut caller: Caller<'_, Host>, ptr: i32, len: i32| {
+            write_str(&mut caller, ptr, len, &helper_funs::get_mode())
+        },
+    )?;
+
+    linker.func_wrap(MODULE, "get_target_fps", || {
+        helper_funs::get_target_fps().map_or(-1, |target_fps| target_fps as i32)
+    })?;
+
+    linker.func_wrap(MODULE, "get_current_fps", || {
+        helper_funs::get_current_fps().unwrap_or(-1.0)
+    })?;
+
+    linker.func_wrap(
+        MODULE,
+        "get_policies",
+        |mut caller: Caller<'_, Host>, ptr: i32, len: i32| {
+            #[derive(Serialize)]
+            struct Policies {
+                policies: Vec<PolicySnapshot>,
+            }
+
+            write_toml(
+                &mut caller,
+                ptr,
+                len,
+                &Policies {
+                    policies: helper_funs::get_policies(),
+                },
+            )
+        },
+    )?;
+
+    linker.func_wrap(MODULE, "get_policy_freq", |policy: i32| {
+        helper_funs::get_policy_freq(policy).map_or(-1, |freq| freq as i64)
+    })?;
+
+    linker.func_wrap(MODULE, "get_util_max", helper_funs::get_util_max)?;
+
+    linker.func_wrap(MODULE, "get_core_temperature", || {
+        helper_funs::get_core_temperature() as i64
+    })?;
+
+    Ok(())
+}
+
+fn register_controls(linker: &mut Linker<Host>) -> WasmResult<()> {
+    linker.func_wrap(
+        MODULE,
+        "set_mode",
+        |caller: Caller<'_, Host>, ptr: i32, len: i32, duration_ptr: i32, duration_len: i32| {
+            let mode = read_str(&caller, ptr, len)?;
+            let duration =
+                (duration_len > 0).then(|| read_str(&caller, duration_ptr, duration_len));
+            helper_funs::set_mode(
+                &caller.data().requester,
+                &mode,
+                duration.transpose()?.as_deref(),
+            )
+            .map_err(|e| to_wasm_error(&e))
+        },
+    )?;
+
+    linker.func_wrap(
+        MODULE,
+        "override_target_fps",
+        |caller: Caller<'_, Host>, target_fps: i32| {
+            helper_funs::override_target_fps(
+                &caller.data().requester,
+                u32::try_from(target_fps).ok(),
+            );
+        },
+    )?;
+
+    linker.func_wrap(
+        MODULE,
+        "set_margin_fps",
+        |caller: Caller<'_, Host>, margin_fps: f64| {
+            helper_funs::set_margin_fps(
+                &caller.data().requester,
+                (margin_fps >= 0.0).then_some(margin_fps),
+            )
+            .map_err(|e| to_wasm_error(&e))
+        },
+    )?;
+
+    linker.func_wrap(
+        MODULE,
+        "pause_fas",
+        |caller: Caller<'_, Host>, seconds: i64| {
+            helper_funs::pause_fas(&caller.data().requester, u64::try_from(seconds).ok());
+        },
+    )?;
+
+    linker.func_wrap(MODULE, "resume_fas", |caller: Caller<'_, Host>| {
+        helper_funs::resume_fas(&caller.data().requester);
+    })?;
+
+    linker.func_wrap(
+        MODULE,
+        "write_node",
+        |caller: Caller<'_, Host>, ptr: i32, len: i32, value_ptr: i32, value_len: i32| {
+            let path = read_str(&caller, ptr, len)?;
+            let value = read_str(&caller, value_ptr, value_len)?;
+            helper_funs::write_node(&path, &value).map_err(|e| to_wasm_error(&e))
+        },
+    )?;
+
+    linker.func_wrap(
+        MODULE,
+        "read_node",
+        |mut caller: Caller<'_, Host>, ptr: i32, len: i32, buffer_ptr: i32, buffer_len: i32| {
+            let path = read_str(&caller, ptr, len)?;
+            let value = helper_funs::read_node(&path).map_err(|e| to_wasm_error(&e))?;
+            write_str(&mut caller, buffer_ptr, buffer_len, &value)
+        },
+    )?;
+
+    linker.func_wrap(
+        MODULE,
+        "decide_control",
+        |mut caller: Caller<'_, Host>, control: i64| {
+            caller.data_mut().decision = Some(ControlDecision::Control(control as isize));
+        },
+    )?;
+
+    // Every call adds a policy, like the table lua returns
+    linker.func_wrap(
+        MODULE,
+        "decide_freq",
+        |mut caller: Caller<'_, Host>, policy: i32, freq: i64| {
+            let decision = &mut caller.data_mut().decision;
+            if let Some(ControlDecision::Freqs(freqs)) = decision {
+                freqs.insert(policy, freq as isize);
+            } else {
+                *decision = Some(ControlDecision::Freqs([(policy, freq as isize)].into()));
+            }
+        },
+    )?;
+
+    Ok(())
+}
+
+// Storage values are strings, other values stored by lua are read as their toml text.
+fn register_runtime(linker: &mut Linker<Host>) -> WasmResult<()> {
+    linker.func_wrap(
+        MODULE,
+        "storage_get",
+        |mut caller: Caller<'_, Host>, ptr: i32, len: i32, buffer_ptr: i32, buffer_len: i32| {
+            let key = read_str(&caller, ptr, len)?;
+            let value = caller.data().storage.get(&key);
+            match value {
+                Some(toml::Value::String(value)) => {
+                    write_str(&mut caller, buffer_ptr, buffer_len, &value)
+                }
+                Some(value) => write_str(&mut caller, buffer_ptr, buffer_len, &value.to_string()),
+                None => Ok(-1),
+            }
+        },
+    )?;
+
+    // A negative `value_len` removes the key
+    linker.func_wrap(
+        MODULE,
+        "storage_set",
+        |caller: Caller<'_, Host>, ptr: i32, len: i32, value_ptr: i32, value_len: i32| {
+            let key = read_str(&caller, ptr, len)?;
+            let value = (value_len >= 0)
+                .then(|| read_str(&caller, value_ptr, value_len))
+                .transpose()?
+                .map(toml::Value::String);
+            caller
+                .data()
+                .storage
+                .set(key, value)
+                .map_err(|e| Error::new(e.to_string()))
+        },
+    )?;
+
+    linker.func_wrap(
+        MODULE,
+        "set_interval",
+        |caller: Caller<'_, Host>, ms: i64| {
+            let delay = Duration::from_millis(ms.max(0) as u64);
+            caller.data().timers.add(delay, true) as i64
+        },
+    )?;
+
+    linker.func_wrap(
+        MODULE,
+        "set_timeout",
+        |caller: Caller<'_, Host>, ms: i64| {
+            let delay = Duration::from_millis(ms.max(0) as u64);
+            caller.data().timers.add(delay, false) as i64
+        },
+    )?;
+
+    linker.func_wrap(
+        MODULE,
+        "clear_timer",
+        |caller: Caller<'_, Host>, id: i64| {
+            caller.data().timers.clear(id as u64);
+        },
+    )?;
+
+    Ok(())
+}
@@ -28,7 +28,7 @@ pub use config::Config;
 #[allow(unused_imports)]
 pub use error::Result;
 #[allow(unused_imports)]
-pub use extension::{Api, Extension, api};
+pub use extension::{Event, Extension, api, ext_test, ext_trust};
 #[allow(unused_imports)]
 pub use node::Mode;
 #[allow(unused_imports)]
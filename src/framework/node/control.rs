@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use super::Node;
+use crate::framework::error::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseState {
+    Running,
+    Paused(Option<Duration>),
+}
+
+impl Node {
+    pub fn get_pause(&mut self) -> Result<PauseState> {
+        let pause = self.get_node("pause")?;
+        let mut parts = pause.split_whitespace();
+
+        match parts.next() {
+            None | Some("0") => Ok(PauseState::Running),
+            Some("1") => {
+                let timeout = parts.next().map(parse_duration).transpose()?;
+                Ok(PauseState::Paused(timeout))
+            }
+            Some(_) => Err(Error::ParseNode),
+        }
+    }
+
+    pub fn get_target_fps_override(&mut self) -> Result<Option<u32>> {
+        let target_fps = self.get_node("target_fps_override")?;
+        let target_fps: u32 = target_fps.trim().parse().map_err(|_| Error::ParseNode)?;
+
+        Ok((target_fps != 0).then_some(target_fps))
+    }
+}
+
+// Accepts plain seconds or a number with an `s` / `m` / `h` suffix, e.g. `300`, `15m`.
+pub fn parse_duration(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let (value, unit) = match s.char_indices().last() {
+        Some((i, 's')) => (&s[..i], 1),
+        Some((i, 'm')) => (&s[..i], 60),
+        Some((i, 'h')) => (&s[..i], 60 * 60),
+        _ => (s, 1),
+    };
+    let value: u64 = value.parse().map_err(|_| Error::ParseNode)?;
+
+    value
+        .checked_mul(unit)
+        .map(Duration::from_secs)
+        .ok_or(Error::ParseNode)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::parse_duration;
+
+    #[test]
+    fn units() {
+        assert_eq!(parse_duration("300").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_duration("45s").unwrap(), Duration::from_secs(45));
+        assert_eq!(
+            parse_duration(" 15m\n").unwrap(),
+            Duration::from_secs(15 * 60)
+        );
+        assert_eq!(
+            parse_duration("2h").unwrap(),
+            Duration::from_secs(2 * 60 * 60)
+        );
+    }
+
+    #[test]
+    fn garbage() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("-5").is_err());
+        assert!(parse_duration("5d").is_err());
+    }
+
+    #[test]
+    fn overflow() {
+        assert_eq!(
+            parse_duration("18446744073709551615").unwrap(),
+            Duration::from_secs(u64::MAX)
+        );
+        assert!(parse_duration("99999999999999999h").is_err());
+        assert!(parse_duration("18446744073709551615m").is_err());
+    }
+}
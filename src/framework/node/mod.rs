@@ -1,3 +1,4 @@
+mod control;
 mod power_mode;
 
 use std::{
@@ -8,6 +9,7 @@ use std::{
 };
 
 use crate::framework::error::{Error, Result};
+pub use control::PauseState;
 use likely_stable::unlikely;
 pub use power_mode::Mode;
 
@@ -31,6 +33,12 @@ impl Node {
         let _ = result.remove_node("mode");
         result.create_node("mode", "balance")?;
 
+        let _ = result.remove_node("pause");
+        result.create_node("pause", "0")?;
+
+        let _ = result.remove_node("target_fps_override");
+        result.create_node("target_fps_override", "0")?;
+
         Ok(result)
     }
 
@@ -86,6 +94,8 @@ impl Node {
             }
         }
 
+        self.timer = Instant::now();
+
         Ok(())
     }
 }
@@ -3,8 +3,11 @@ use std::{
     str::FromStr,
 };
 
-use super::Node;
-use crate::framework::error::{Error, Result};
+use super::{Node, control::parse_duration};
+use crate::framework::{
+    config::ModeDuration,
+    error::{Error, Result},
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Mode {
@@ -41,10 +44,36 @@ impl Display for Mode {
     }
 }
 
+impl FromStr for ModeDuration {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "never" => Self::Permanent,
+            "game" => Self::GameSession,
+            _ => Self::Secs(parse_duration(s)?.as_secs()),
+        })
+    }
+}
+
+impl Display for ModeDuration {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Permanent => write!(f, "never"),
+            Self::GameSession => write!(f, "game"),
+            Self::Secs(secs) => write!(f, "{secs}"),
+        }
+    }
+}
+
 impl Node {
-    pub fn get_mode(&mut self) -> Result<Mode> {
+    pub fn get_mode(&mut self) -> Result<(Mode, Option<ModeDuration>)> {
         let mode = self.get_node("mode").or(Err(Error::NodeNotFound))?;
+        let mut parts = mode.split_whitespace();
+
+        let mode = Mode::from_str(parts.next().ok_or(Error::ParseNode)?)?;
+        let duration = parts.next().map(ModeDuration::from_str).transpose()?;
 
-        Mode::from_str(mode.trim())
+        Ok((mode, duration))
     }
 }
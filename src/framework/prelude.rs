@@ -16,4 +16,6 @@
 // with fas-rs. If not, see <https://www.gnu.org/licenses/>.
 
 #![allow(unused_imports)]
-pub use super::{Api, Extension, Scheduler, api, config::Config, node::Mode};
+pub use super::{
+    Event, Extension, Scheduler, api, config::Config, ext_test, ext_trust, node::Mode,
+};
@@ -21,7 +21,7 @@ use likely_stable::unlikely;
 #[cfg(debug_assertions)]
 use log::debug;
 
-use super::Buffer;
+use super::{Buffer, FramePacing, JANK_FRAMETIME_RATIO};
 use crate::{Extension, api::trigger_target_fps_change, framework::config::TargetFps};
 
 impl Buffer {
@@ -72,6 +72,53 @@ impl Buffer {
             .unwrap_or_default()
     }
 
+    pub fn calculate_pacing(&mut self) {
+        let Some(target_fps) = self
+            .target_fps_state
+            .target_fps
+            .filter(|_| !self.frametime_state.frametimes.is_empty())
+        else {
+            self.frametime_state.pacing = FramePacing::default();
+            return;
+        };
+
+        let mut sorted: Vec<_> = self.frametime_state.frametimes.iter().copied().collect();
+        sorted.sort_unstable_by(|a, b| b.cmp(a));
+
+        let low = |ratio: f64| {
+            let count = ((sorted.len() as f64 * ratio).ceil() as usize).max(1);
+            let average = sorted.iter().take(count).sum::<Duration>() / count as u32;
+            1.0 / average.as_secs_f64()
+        };
+        let over = |ratio: f64| {
+            sorted
+                .iter()
+                .take_while(|frametime| frametime.as_secs_f64() * f64::from(target_fps) > ratio)
+                .count()
+        };
+
+        let mean =
+            sorted.iter().map(Duration::as_secs_f64).sum::<f64>() * 1000.0 / sorted.len() as f64;
+        let variance = sorted
+            .iter()
+            .map(|frametime| (frametime.as_secs_f64() * 1000.0 - mean).powi(2))
+            .sum::<f64>()
+            / sorted.len() as f64;
+
+        let pacing = FramePacing {
+            low_1: low(0.01),
+            low_01: low(0.001),
+            over_1_5x: over(JANK_FRAMETIME_RATIO),
+            over_2x: over(2.0),
+            variance,
+        };
+
+        #[cfg(debug_assertions)]
+        debug!("frame pacing: {pacing:?}");
+
+        self.frametime_state.pacing = pacing;
+    }
+
     pub fn calculate_target_fps(&mut self, extension: &Extension) {
         let new_target_fps = self.target_fps();
         if self.target_fps_state.target_fps != new_target_fps || new_target_fps.is_none() {
@@ -93,6 +140,10 @@ impl Buffer {
     }
 
     fn target_fps(&self) -> Option<u32> {
+        if let Some(target_fps) = self.target_fps_state.target_fps_override {
+            return Some(target_fps);
+        }
+
         let target_fpses = match &self.target_fps_state.target_fps_config {
             TargetFps::Value(t) => vec![*t],
             TargetFps::Array(arr) => arr.clone(),
@@ -25,7 +25,14 @@ use std::{
 use libc::pid_t;
 use likely_stable::unlikely;
 
-use crate::{Extension, framework::config::TargetFps};
+use crate::{
+    Extension,
+    api::{trigger_frame, trigger_jank},
+    framework::config::TargetFps,
+};
+
+// A single frame longer than this many target frametimes is reported as jank.
+pub const JANK_FRAMETIME_RATIO: f64 = 1.5;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum BufferWorkingState {
@@ -39,6 +46,18 @@ pub struct PackageInfo {
     pub pkg: String,
 }
 
+// Over the whole frame window, refreshed together with the target fps.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FramePacing {
+    // Average fps of the slowest 1% / 0.1% of the frames
+    pub low_1: f64,
+    pub low_01: f64,
+    pub over_1_5x: usize,
+    pub over_2x: usize,
+    // ms²
+    pub variance: f64,
+}
+
 #[derive(Debug)]
 pub struct FrameTimeState {
     pub current_fps_long: f64,
@@ -47,6 +66,7 @@ pub struct FrameTimeState {
     pub avg_time_short: Duration,
     pub frametimes: VecDeque<Duration>,
     pub additional_frametime: Duration,
+    pub pacing: FramePacing,
 }
 
 impl FrameTimeState {
@@ -58,6 +78,7 @@ impl FrameTimeState {
             avg_time_short: Duration::ZERO,
             frametimes: VecDeque::with_capacity(1440),
             additional_frametime: Duration::ZERO,
+            pacing: FramePacing::default(),
         }
     }
 }
@@ -66,6 +87,7 @@ impl FrameTimeState {
 pub struct TargetFpsState {
     pub target_fps: Option<u32>,
     target_fps_config: TargetFps,
+    target_fps_override: Option<u32>,
 }
 
 impl TargetFpsState {
@@ -73,6 +95,7 @@ impl TargetFpsState {
         Self {
             target_fps: None,
             target_fps_config,
+            target_fps_override: None,
         }
     }
 }
@@ -128,6 +151,25 @@ impl Buffer {
 
         self.frametime_state.frametimes.push_front(d);
         self.try_calculate(extension);
+        self.trigger_frame(d, extension);
+    }
+
+    fn trigger_frame(&self, d: Duration, extension: &Extension) {
+        let Some(target_fps) = self.target_fps_state.target_fps else {
+            return;
+        };
+
+        trigger_frame(
+            extension,
+            d,
+            self.frametime_state.current_fps_short,
+            self.frametime_state.current_fps_long,
+            target_fps,
+        );
+
+        if d.as_secs_f64() * f64::from(target_fps) > JANK_FRAMETIME_RATIO {
+            trigger_jank(extension, d, target_fps);
+        }
     }
 
     fn try_calculate(&mut self, extension: &Extension) {
@@ -135,6 +177,7 @@ impl Buffer {
         if unlikely(self.state.calculate_timer.elapsed() >= Duration::from_millis(100)) {
             self.state.calculate_timer = Instant::now();
             self.calculate_target_fps(extension);
+            self.calculate_pacing();
         }
     }
 
@@ -151,6 +194,10 @@ impl Buffer {
         self.state.working_state_timer = Instant::now();
     }
 
+    pub const fn set_target_fps_override(&mut self, target_fps: Option<u32>) {
+        self.target_fps_state.target_fps_override = target_fps;
+    }
+
     pub fn additional_frametime(&mut self, extension: &Extension) {
         self.frametime_state.additional_frametime = self.state.last_update.elapsed();
         self.try_calculate(extension);
@@ -0,0 +1,209 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::time::{Duration, Instant};
+
+use log::info;
+
+use super::Looper;
+use crate::{
+    api::request::Request,
+    framework::{
+        config::ModeDuration,
+        node::{Mode, PauseState},
+    },
+};
+
+#[derive(Debug, Clone, Copy)]
+pub enum ModeExpiry {
+    At(Instant),
+    GameSession,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ModeOverride {
+    pub previous: Mode,
+    pub expiry: ModeExpiry,
+}
+
+pub struct ControlState {
+    pub pause: PauseState,
+    pub pause_deadline: Option<Instant>,
+    pub target_fps_override: Option<u32>,
+    pub mode_override: Option<ModeOverride>,
+}
+
+impl ControlState {
+    pub const fn new() -> Self {
+        Self {
+            pause: PauseState::Running,
+            pause_deadline: None,
+            target_fps_override: None,
+            mode_override: None,
+        }
+    }
+
+    pub const fn paused(&self) -> bool {
+        matches!(self.pause, PauseState::Paused(_))
+    }
+}
+
+impl Looper {
+    pub fn apply_extension_requests(&mut self) {
+        let requests: Vec<_> = self.extension.requests().collect();
+
+        for (extension, request) in requests {
+            info!("Extension '{extension}' requested: {request:?}");
+
+            let _ = match request {
+                Request::SetMode(mode, None) => {
+                    self.node.create_node("mode", mode.to_string().as_str())
+                }
+                Request::SetMode(mode, Some(duration)) => self
+                    .node
+                    .create_node("mode", format!("{mode} {duration}").as_str()),
+                Request::TargetFpsOverride(target_fps) => self.node.create_node(
+                    "target_fps_override",
+                    target_fps.unwrap_or_default().to_string().as_str(),
+                ),
+                Request::Pause(None) => self.node.create_node("pause", "1"),
+                Request::Pause(Some(timeout)) => self
+                    .node
+                    .create_node("pause", format!("1 {}", timeout.as_secs()).as_str()),
+                Request::Resume => self.node.create_node("pause", "0"),
+                Request::MarginFps(margin_fps) => {
+                    self.controller_state.margin_fps_override = margin_fps;
+                    Ok(())
+                }
+            };
+        }
+    }
+
+    pub fn update_control_nodes(&mut self) {
+        self.update_pause();
+        self.update_target_fps_override();
+    }
+
+    fn update_pause(&mut self) {
+        let Ok(pause) = self.node.get_pause() else {
+            return;
+        };
+
+        if pause != self.control_state.pause {
+            match pause {
+                PauseState::Running => info!("Resume fas"),
+                PauseState::Paused(None) => info!("Pause fas until cleared"),
+                PauseState::Paused(Some(timeout)) => info!("Pause fas for {timeout:?}"),
+            }
+
+            self.control_state.pause = pause;
+            self.control_state.pause_deadline = match pause {
+                // Too far out to represent is as good as until cleared
+                PauseState::Paused(Some(timeout)) => Instant::now().checked_add(timeout),
+                _ => None,
+            };
+        }
+
+        if self
+            .control_state
+            .pause_deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            info!("Pause timed out, resume fas");
+            self.control_state.pause = PauseState::Running;
+            self.control_state.pause_deadline = None;
+            let _ = self.node.create_node("pause", "0");
+        }
+    }
+
+    fn update_target_fps_override(&mut self) {
+        let Ok(target_fps_override) = self.node.get_target_fps_override() else {
+            return;
+        };
+
+        if target_fps_override != self.control_state.target_fps_override {
+            match target_fps_override {
+                Some(target_fps) => info!("Override target fps: {target_fps}"),
+                None => info!("Clear target fps override"),
+            }
+
+            self.control_state.target_fps_override = target_fps_override;
+        }
+
+        if let Some(buffer) = self.fas_state.buffer.as_mut() {
+            buffer.set_target_fps_override(target_fps_override);
+        }
+    }
+
+    pub fn set_mode_override(&mut self, new_mode: Mode, duration: ModeDuration) {
+        let previous = self
+            .control_state
+            .mode_override
+            .map_or(self.fas_state.mode, |mode_override| mode_override.previous);
+        let expiry = match duration {
+            ModeDuration::Permanent => None,
+            ModeDuration::GameSession if self.fas_state.buffer.is_some() => {
+                Some(ModeExpiry::GameSession)
+            }
+            // Otherwise it would linger until some later game exits
+            ModeDuration::GameSession => {
+                info!("No game is running, mode {new_mode} stays");
+                None
+            }
+            // Too far out to represent is as good as permanent
+            ModeDuration::Secs(secs) => Instant::now()
+                .checked_add(Duration::from_secs(secs))
+                .map(ModeExpiry::At),
+        };
+
+        self.control_state.mode_override = expiry.filter(|_| previous != new_mode).map(|expiry| {
+            info!("Mode {new_mode} is temporary ({duration:?}), will return to {previous}");
+            ModeOverride { previous, expiry }
+        });
+    }
+
+    pub fn check_mode_override(&mut self) {
+        if let Some(ModeOverride {
+            expiry: ModeExpiry::At(deadline),
+            ..
+        }) = self.control_state.mode_override
+            && Instant::now() >= deadline
+        {
+            self.revert_mode();
+        }
+    }
+
+    pub fn end_game_session_override(&mut self) {
+        if let Some(ModeOverride {
+            expiry: ModeExpiry::GameSession,
+            ..
+        }) = self.control_state.mode_override
+        {
+            self.revert_mode();
+        }
+    }
+
+    fn revert_mode(&mut self) {
+        if let Some(mode_override) = self.control_state.mode_override.take() {
+            info!("Mode override expired");
+            self.apply_mode(mode_override.previous);
+            let _ = self
+                .node
+                .create_node("mode", mode_override.previous.to_string().as_str());
+        }
+    }
+}
@@ -17,7 +17,9 @@
 
 mod buffer;
 mod clean;
+mod control;
 mod policy;
+mod rules;
 
 use std::time::{Duration, Instant};
 
@@ -26,15 +28,22 @@ use likely_stable::{likely, unlikely};
 #[cfg(debug_assertions)]
 use log::debug;
 use log::info;
-use policy::{ControllerParams, controll::calculate_control};
+use policy::{
+    controll::{calculate_control, control_input, is_janked},
+    strategy::StrategyState,
+};
+use rules::{Facts, Rules};
 
 use super::{FasData, thermal::Thermal, topapp::TopAppsWatcher};
 use crate::{
     Controller,
-    api::{trigger_load_fas, trigger_start_fas, trigger_stop_fas, trigger_unload_fas},
+    api::{
+        control::ControlDecision, snapshot::SNAPSHOT, trigger_load_fas, trigger_mode_change,
+        trigger_start_fas, trigger_stop_fas, trigger_top_app_change, trigger_unload_fas,
+    },
     framework::{
         Extension,
-        config::Config,
+        config::{Config, Strategy},
         error::Result,
         node::{Mode, Node},
         pid_utils::get_process_name,
@@ -43,8 +52,10 @@ use crate::{
 
 use buffer::{Buffer, BufferWorkingState};
 use clean::Cleaner;
+use control::ControlState;
 
 const DELAY_TIME: Duration = Duration::from_secs(3);
+const SNAPSHOT_REFRESH: Duration = Duration::from_secs(1);
 
 #[derive(PartialEq)]
 enum State {
@@ -55,6 +66,7 @@ enum State {
 
 struct FasState {
     mode: Mode,
+    top_app: Option<(i32, String)>,
     working_state: State,
     delay_timer: Instant,
     buffer: Option<Buffer>,
@@ -68,8 +80,9 @@ struct AnalyzerState {
 
 struct ControllerState {
     controller: Controller,
-    params: ControllerParams,
+    strategy: StrategyState,
     target_fps_offset: f64,
+    margin_fps_override: Option<f64>,
     usage_sample_timer: Instant,
 }
 
@@ -83,6 +96,9 @@ pub struct Looper {
     cleaner: Cleaner,
     fas_state: FasState,
     controller_state: ControllerState,
+    control_state: ControlState,
+    rules: Rules,
+    snapshot_timer: Instant,
 }
 
 impl Looper {
@@ -93,6 +109,7 @@ impl Looper {
         extension: Extension,
         controller: Controller,
     ) -> Self {
+        let rules = Rules::new(extension.requester("rules"));
         Self {
             analyzer_state: AnalyzerState {
                 analyzer,
@@ -107,22 +124,32 @@ impl Looper {
             cleaner: Cleaner::new(),
             fas_state: FasState {
                 mode: Mode::Balance,
+                top_app: None,
                 buffer: None,
                 working_state: State::NotWorking,
                 delay_timer: Instant::now(),
             },
             controller_state: ControllerState {
                 controller,
-                params: ControllerParams::default(),
+                strategy: StrategyState::new(Strategy::default()),
                 target_fps_offset: 0.0,
+                margin_fps_override: None,
                 usage_sample_timer: Instant::now(),
             },
+            control_state: ControlState::new(),
+            rules,
+            snapshot_timer: Instant::now(),
         }
     }
 
     pub fn enter_loop(&mut self) -> Result<()> {
         loop {
+            self.apply_extension_requests();
+            self.update_extension_settings();
             self.switch_mode();
+            self.update_control_nodes();
+            self.update_rules();
+            self.update_top_app();
             let _ = self.update_analyzer();
             self.retain_topapp();
 
@@ -152,23 +179,94 @@ impl Looper {
                     BufferWorkingState::Usable => self.do_policy(),
                 }
             }
+
+            self.publish_snapshot();
+        }
+    }
+
+    fn update_extension_settings(&mut self) {
+        if let Some(settings) = self.config.extension_settings_changed() {
+            self.extension.update_settings(settings);
+        }
+    }
+
+    fn update_rules(&mut self) {
+        if !self.rules.due() {
+            return;
+        }
+
+        // The snapshot can be a second old, too late for a rule that guards against heat
+        if self.rules.uses_temperature() {
+            self.therminal.temperature_update();
+        }
+
+        let buffer = self.fas_state.buffer.as_ref();
+        self.rules.update(&Facts {
+            package: buffer.map(|buffer| buffer.package_info.pkg.as_str()),
+            mode: self.fas_state.mode,
+            target_fps: buffer.and_then(|buffer| buffer.target_fps_state.target_fps),
+            temperature: self.therminal.core_temperature(),
+            target_fps_override: self.control_state.target_fps_override,
+        });
+    }
+
+    fn publish_snapshot(&mut self) {
+        // The policy only reads them while a game runs, extensions want them at any time
+        if self.extension.any_loaded() && self.snapshot_timer.elapsed() >= SNAPSHOT_REFRESH {
+            self.snapshot_timer = Instant::now();
+            self.therminal.temperature_update();
+            self.controller_state.controller.publish_cur_freqs();
         }
+
+        let mut snapshot = SNAPSHOT.write();
+        snapshot.mode = self.fas_state.mode;
+        snapshot.target_fps = self
+            .fas_state
+            .buffer
+            .as_ref()
+            .and_then(|buffer| buffer.target_fps_state.target_fps);
+        snapshot.current_fps = self
+            .fas_state
+            .buffer
+            .as_ref()
+            .map(|buffer| buffer.frametime_state.current_fps_long);
     }
 
     fn switch_mode(&mut self) {
-        if let Ok(new_mode) = self.node.get_mode() {
-            if likely(self.fas_state.mode != new_mode) {
-                info!("Switch mode: {} -> {}", self.fas_state.mode, new_mode);
-                self.fas_state.mode = new_mode;
+        if let Ok((new_mode, duration)) = self.node.get_mode() {
+            let changed = self.fas_state.mode != new_mode;
 
-                if self.fas_state.working_state == State::Working {
-                    self.controller_state.controller.init_game(
-                        self.fas_state.buffer.as_ref().unwrap().package_info.pid,
-                        &self.extension,
-                    );
-                }
+            // Naming the current mode with a duration extends (or ends) the running override
+            if changed || duration.is_some() {
+                let duration = duration.unwrap_or(self.config.mode_config(new_mode).revert_after);
+                self.set_mode_override(new_mode, duration);
+            }
+
+            if likely(changed) {
+                self.apply_mode(new_mode);
+            }
+
+            if duration.is_some() {
+                let _ = self.node.create_node("mode", new_mode.to_string().as_str());
             }
         }
+
+        self.check_mode_override();
+    }
+
+    fn apply_mode(&mut self, new_mode: Mode) {
+        let old_mode = self.fas_state.mode;
+        info!("Switch mode: {old_mode} -> {new_mode}");
+        self.fas_state.mode = new_mode;
+        trigger_mode_change(&self.extension, old_mode, new_mode);
+
+        if self.fas_state.working_state == State::Working {
+            self.controller_state.strategy.reset();
+            self.controller_state.controller.init_game(
+                self.fas_state.buffer.as_ref().unwrap().package_info.pid,
+                &self.extension,
+            );
+        }
     }
 
     fn recv_message(&mut self) -> Option<FasData> {
@@ -208,28 +306,84 @@ impl Looper {
             return;
         }
 
-        let (control, is_janked) = if let Some(buffer) = &self.fas_state.buffer {
-            let target_fps_offset = self
-                .therminal
-                .target_fps_offset(&mut self.config, self.fas_state.mode);
-            calculate_control(
-                buffer,
-                &mut self.config,
-                self.fas_state.mode,
-                &mut self.controller_state,
-                target_fps_offset,
-            )
-            .unwrap_or_default()
-        } else {
+        let Some(buffer) = &self.fas_state.buffer else {
             return;
         };
 
+        let target_fps_offset = self.therminal.target_fps_offset(
+            &mut self.config,
+            self.fas_state.mode,
+            &self.extension,
+        );
+        let is_janked = is_janked(
+            buffer,
+            &mut self.config,
+            self.fas_state.mode,
+            target_fps_offset,
+        );
+        let decision = control_input(
+            buffer,
+            &mut self.config,
+            self.fas_state.mode,
+            &self.controller_state,
+            target_fps_offset,
+            is_janked,
+        )
+        .and_then(|input| self.extension.compute_control(input));
+
         #[cfg(debug_assertions)]
-        debug!("control: {control}khz");
+        debug!("extension decision: {decision:?}");
+
+        // The strategy only runs on frames it controls, and starts over once it takes back over
+        match decision {
+            Some(ControlDecision::Freqs(freqs)) => {
+                self.controller_state.strategy.reset();
+                self.controller_state.controller.fas_set_freqs(&freqs);
+            }
+            Some(ControlDecision::Control(control)) => {
+                self.controller_state.strategy.reset();
+                self.controller_state
+                    .controller
+                    .fas_update_freq(control, is_janked);
+            }
+            None => {
+                let control = calculate_control(
+                    buffer,
+                    &mut self.config,
+                    self.fas_state.mode,
+                    &mut self.controller_state,
+                    target_fps_offset,
+                )
+                .unwrap_or_default();
+
+                #[cfg(debug_assertions)]
+                debug!("control: {control}khz");
+
+                self.controller_state
+                    .controller
+                    .fas_update_freq(control, is_janked);
+            }
+        }
+    }
+
+    fn update_top_app(&mut self) {
+        let Some((pid, pkg)) = self.windows_watcher.top_app() else {
+            return;
+        };
+
+        if self
+            .fas_state
+            .top_app
+            .as_ref()
+            .is_some_and(|(top_pid, top_pkg)| *top_pid == pid && top_pkg == pkg)
+        {
+            return;
+        }
 
-        self.controller_state
-            .controller
-            .fas_update_freq(control, is_janked);
+        let pkg = pkg.to_string();
+        let is_game = self.config.need_fas(&pkg);
+        trigger_top_app_change(&self.extension, pid, pkg.clone(), is_game);
+        self.fas_state.top_app = Some((pid, pkg));
     }
 
     pub fn retain_topapp(&mut self) {
@@ -246,10 +400,11 @@ impl Looper {
                 let pkg = buffer.package_info.pkg.clone();
                 trigger_unload_fas(&self.extension, buffer.package_info.pid, pkg);
                 self.fas_state.buffer = None;
+                self.end_game_session_override();
             }
         }
 
-        if self.fas_state.buffer.is_none() {
+        if self.fas_state.buffer.is_none() || self.control_state.paused() {
             self.disable_fas();
         } else {
             self.enable_fas();
@@ -283,6 +438,7 @@ impl Looper {
                     self.fas_state.working_state = State::Working;
                     self.cleaner.cleanup();
                     self.controller_state.target_fps_offset = 0.0;
+                    self.controller_state.strategy.reset();
                     self.controller_state.controller.init_game(
                         self.fas_state.buffer.as_ref().unwrap().package_info.pid,
                         &self.extension,
@@ -317,6 +473,7 @@ impl Looper {
             trigger_load_fas(&self.extension, pid, pkg.clone());
 
             let mut buffer = Buffer::new(target_fps, pid, pkg);
+            buffer.set_target_fps_override(self.control_state.target_fps_override);
             buffer.push_frametime(frametime, &self.extension);
 
             self.fas_state.buffer = Some(buffer);
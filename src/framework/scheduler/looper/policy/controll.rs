@@ -21,8 +21,13 @@ use likely_stable::unlikely;
 #[cfg(debug_assertions)]
 use log::debug;
 
-use super::super::buffer::Buffer;
-use crate::framework::{config::MarginFps, prelude::*, scheduler::looper::ControllerState};
+use super::super::buffer::{Buffer, FrameTimeState};
+use crate::framework::{
+    api::control::ControlInput,
+    config::{JankConfig, MarginFps},
+    prelude::*,
+    scheduler::looper::ControllerState,
+};
 
 pub fn calculate_control(
     buffer: &Buffer,
@@ -30,22 +35,13 @@ pub fn calculate_control(
     mode: Mode,
     controller_state: &mut ControllerState,
     target_fps_offset_thermal: f64,
-) -> Option<(isize, bool)> // control, is_janked
-{
+) -> Option<isize> {
     if unlikely(buffer.frametime_state.frametimes.len() < 60) {
         return None;
     }
 
     let target_fps = f64::from(buffer.target_fps_state.target_fps?);
-    let margin_fps: f64 = match &config.mode_config(mode).margin_fps {
-        MarginFps::BaseOnly(base) => target_fps / 60.0 * f64::from(*base),
-        MarginFps::Advanced { base, overrides } => overrides
-            .get(&target_fps.to_string())
-            .copied()
-            .map_or_else(|| target_fps / 60.0 * f64::from(*base), f64::from),
-    };
-
-    assert!(margin_fps.is_sign_positive(), "margin_fps must be positive");
+    let margin_fps = margin_fps(target_fps, config, mode, controller_state);
 
     let target_fps = (target_fps + target_fps_offset_thermal).clamp(0.0, target_fps);
     let adjusted_target_fps = adjust_target_fps(target_fps, controller_state) - margin_fps;
@@ -59,12 +55,112 @@ pub fn calculate_control(
         debug!("target_frametime: {target_frametime:?}");
     }
 
-    Some((
-        calculate_control_inner(controller_state, adjusted_last_frame, target_frametime),
-        buffer.frametime_state.current_fps_long < target_fps - 2.0,
+    let strategy = config.strategy(&buffer.package_info.pkg, mode);
+    controller_state.strategy.select(strategy);
+    let error = adjusted_last_frame.as_nanos() as f64 - target_frametime.as_nanos() as f64;
+
+    Some(controller_state.strategy.control(
+        error,
+        config.mode_config(mode),
+        &controller_state.controller,
     ))
 }
 
+// Apart from `calculate_control`, which steps the strategy, so it can be left out when an
+// extension decides.
+pub fn is_janked(
+    buffer: &Buffer,
+    config: &mut Config,
+    mode: Mode,
+    target_fps_offset_thermal: f64,
+) -> bool {
+    if unlikely(buffer.frametime_state.frametimes.len() < 60) {
+        return false;
+    }
+
+    buffer
+        .target_fps_state
+        .target_fps
+        .is_some_and(|target_fps| {
+            let target_fps = f64::from(target_fps);
+            frames_janked(
+                &buffer.frametime_state,
+                (target_fps + target_fps_offset_thermal).clamp(0.0, target_fps),
+                &config.mode_config(mode).jank,
+            )
+        })
+}
+
+fn frames_janked(frametime_state: &FrameTimeState, target_fps: f64, jank: &JankConfig) -> bool {
+    let pacing = &frametime_state.pacing;
+
+    jank.fps_below
+        .is_some_and(|fps_below| frametime_state.current_fps_long < target_fps - fps_below)
+        || jank
+            .low_1
+            .is_some_and(|ratio| pacing.low_1 < target_fps * ratio)
+        || jank
+            .low_01
+            .is_some_and(|ratio| pacing.low_01 < target_fps * ratio)
+        || jank
+            .over_1_5x
+            .is_some_and(|count| pacing.over_1_5x >= count)
+        || jank.over_2x.is_some_and(|count| pacing.over_2x >= count)
+        || jank
+            .variance
+            .is_some_and(|variance| pacing.variance > variance)
+}
+
+// The same view of the frame window the built-in controller works on, for `compute_control`.
+pub fn control_input(
+    buffer: &Buffer,
+    config: &mut Config,
+    mode: Mode,
+    controller_state: &ControllerState,
+    target_fps_offset_thermal: f64,
+    is_janked: bool,
+) -> Option<ControlInput> {
+    if unlikely(buffer.frametime_state.frametimes.len() < 60) {
+        return None;
+    }
+
+    let target_fps = f64::from(buffer.target_fps_state.target_fps?);
+    let margin_fps = margin_fps(target_fps, config, mode, controller_state);
+
+    Some(ControlInput {
+        frametimes: buffer
+            .frametime_state
+            .frametimes
+            .iter()
+            .map(|frametime| frametime.as_nanos() as u64)
+            .collect(),
+        target_fps: (target_fps + target_fps_offset_thermal).clamp(0.0, target_fps),
+        margin_fps,
+        fps_short: buffer.frametime_state.current_fps_short,
+        fps_long: buffer.frametime_state.current_fps_long,
+        is_janked,
+    })
+}
+
+fn margin_fps(
+    target_fps: f64,
+    config: &mut Config,
+    mode: Mode,
+    controller_state: &ControllerState,
+) -> f64 {
+    // Extensions set the override, never trust it to be usable
+    controller_state
+        .margin_fps_override
+        .filter(|margin_fps| margin_fps.is_finite() && margin_fps.is_sign_positive())
+        .unwrap_or_else(|| match &config.mode_config(mode).margin_fps {
+            MarginFps::BaseOnly(base) => target_fps / 60.0 * f64::from(*base),
+            MarginFps::Advanced { base, overrides } => overrides
+                .get(&target_fps.to_string())
+                .copied()
+                .map_or_else(|| target_fps / 60.0 * f64::from(*base), f64::from),
+        })
+}
+
 fn get_normalized_last_frame(buffer: &Buffer, target_fps: f64) -> Duration {
     let last_frame = buffer
         .frametime_state
@@ -98,17 +194,3 @@ fn adjust_target_fps(target_fps: f64, controller_state: &mut ControllerState) ->
     controller_state.target_fps_offset = controller_state.target_fps_offset.clamp(-3.0, 0.0);
     target_fps + controller_state.target_fps_offset
 }
-
-fn calculate_control_inner(
-    controller_state: &ControllerState,
-    current_frametime: Duration,
-    target_frametime: Duration,
-) -> isize {
-    let error_p = (current_frametime.as_nanos() as f64 - target_frametime.as_nanos() as f64)
-        * controller_state.params.kp;
-
-    #[cfg(debug_assertions)]
-    debug!("error_p {error_p}");
-
-    error_p as isize
-}
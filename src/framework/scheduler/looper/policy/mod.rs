@@ -16,14 +16,4 @@
 // with fas-rs. If not, see <https://www.gnu.org/licenses/>.
 
 pub mod controll;
-
-#[derive(Debug, Copy, Clone)]
-pub struct ControllerParams {
-    pub kp: f64,
-}
-
-impl Default for ControllerParams {
-    fn default() -> Self {
-        Self { kp: 0.000_3 }
-    }
-}
+pub mod strategy;
@@ -0,0 +1,157 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(debug_assertions)]
+use log::debug;
+use log::{info, warn};
+
+use crate::{
+    Controller,
+    framework::config::{ModeConfig, Strategy},
+};
+
+// Turns the frame time error into a frequency change (khz) for `Controller::fas_update_freq`.
+//
+// `error` is how far the last frame, normalized to the target fps, is off one second, in ns.
+// The parameters are read from `mode_config` on every frame so config changes apply at once.
+pub trait ControlStrategy {
+    fn control(&mut self, error: f64, mode_config: &ModeConfig, controller: &Controller) -> isize;
+
+    // Called whenever a game (re)starts or the mode changes.
+    fn reset(&mut self) {}
+}
+
+struct Proportional;
+
+impl ControlStrategy for Proportional {
+    fn control(&mut self, error: f64, mode_config: &ModeConfig, _: &Controller) -> isize {
+        let error_p = error * mode_config.kp;
+
+        #[cfg(debug_assertions)]
+        debug!("error_p {error_p}");
+
+        error_p as isize
+    }
+}
+
+#[derive(Default)]
+struct Pid {
+    integral: f64,
+    last_error: Option<f64>,
+}
+
+impl ControlStrategy for Pid {
+    fn control(&mut self, error: f64, mode_config: &ModeConfig, controller: &Controller) -> isize {
+        let derivative = self.last_error.map_or(0.0, |last_error| error - last_error);
+        self.last_error = Some(error);
+
+        // Anti-windup: while the frequency is already pinned in the direction the error pushes,
+        // integrating would only build up a debt that overshoots once the load changes.
+        if !controller.freq_saturated(error as isize) {
+            self.integral += error;
+        }
+
+        let error_p = error * mode_config.kp;
+        let error_i = self.integral * mode_config.ki;
+        let error_d = derivative * mode_config.kd;
+
+        #[cfg(debug_assertions)]
+        debug!("error_p {error_p}, error_i {error_i}, error_d {error_d}");
+
+        (error_p + error_i + error_d) as isize
+    }
+
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+// `steps` maps the error in percent of the target frame time to a fixed frequency step.
+struct Stepping;
+
+impl ControlStrategy for Stepping {
+    fn control(&mut self, error: f64, mode_config: &ModeConfig, _: &Controller) -> isize {
+        let error_percent = error / 10_000_000.0;
+        let step = mode_config
+            .steps
+            .iter()
+            .rev()
+            .find(|(threshold, _)| error_percent >= *threshold)
+            .or_else(|| mode_config.steps.first())
+            .map_or(0, |(_, step)| *step);
+
+        #[cfg(debug_assertions)]
+        debug!("error {error_percent:.2}%, step {step}");
+
+        step
+    }
+}
+
+pub struct StrategyState {
+    kind: Strategy,
+    strategy: Box<dyn ControlStrategy>,
+    warned: bool,
+}
+
+impl StrategyState {
+    pub fn new(kind: Strategy) -> Self {
+        let strategy: Box<dyn ControlStrategy> = match kind {
+            Strategy::P => Box::new(Proportional),
+            Strategy::Pid => Box::new(Pid::default()),
+            Strategy::Step => Box::new(Stepping),
+        };
+
+        Self {
+            kind,
+            strategy,
+            warned: false,
+        }
+    }
+
+    // Starts over with a fresh strategy when the config now asks for another one.
+    pub fn select(&mut self, kind: Strategy) {
+        if kind != self.kind {
+            info!("Switch control strategy: {:?} -> {kind:?}", self.kind);
+            *self = Self::new(kind);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.strategy.reset();
+    }
+
+    pub fn control(
+        &mut self,
+        error: f64,
+        mode_config: &ModeConfig,
+        controller: &Controller,
+    ) -> isize {
+        // The default strategy is "p", tuning ki / kd alone would seem to do nothing at all
+        if !self.warned
+            && self.kind != Strategy::Pid
+            && (mode_config.ki != 0.0 || mode_config.kd != 0.0)
+        {
+            warn!(
+                "ki / kd are only used by the pid strategy, the current {:?} strategy ignores them",
+                self.kind
+            );
+            self.warned = true;
+        }
+
+        self.strategy.control(error, mode_config, controller)
+    }
+}
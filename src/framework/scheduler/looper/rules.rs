@@ -0,0 +1,336 @@
+// Copyright 2026-2026, shadow3aaa
+//
+// This file is part of fas-rs.
+//
+// fas-rs is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License as published by the Free
+// Software Foundation, either version 3 of the License, or (at your option)
+// any later version.
+//
+// fas-rs is distributed in the hope that it will be useful, but WITHOUT ANY
+// WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along
+// with fas-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, slice,
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::Result;
+use log::{error, info, warn};
+use serde::Deserialize;
+
+use crate::{
+    api::{helper_funs, request::Requester},
+    cpu_common::{EXTRA_POLICY_MAP, IGNORE_MAP},
+    framework::node::Mode,
+};
+
+// [[rule]]
+// when = { package = "com.example.game", mode = ["balance", "powersave"], charging = false }
+// then = { extra_policy = [{ policy = 7, max = 2400000 }], ignore_policy = [0] }
+const RULES_PATH: &str = "/sdcard/Android/fas-rs/rules.toml";
+const CHARGING_STATUS: &str = "/sys/class/power_supply/battery/status";
+const EVAL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Default, Deserialize)]
+struct RuleFile {
+    #[serde(default, rename = "rule")]
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Rule {
+    #[serde(default)]
+    when: Conditions,
+    #[serde(default)]
+    then: Actions,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T: PartialEq> OneOrMany<T> {
+    fn contains(&self, value: &T) -> bool {
+        match self {
+            Self::One(one) => one == value,
+            Self::Many(many) => many.contains(value),
+        }
+    }
+
+    fn iter(&self) -> slice::Iter<'_, T> {
+        match self {
+            Self::One(one) => slice::from_ref(one).iter(),
+            Self::Many(many) => many.iter(),
+        }
+    }
+}
+
+// Every condition given has to hold, a rule without any always applies.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Conditions {
+    package: Option<OneOrMany<String>>,
+    mode: Option<OneOrMany<String>>,
+    target_fps: Option<OneOrMany<u32>>,
+    temperature_above: Option<u64>,
+    temperature_below: Option<u64>,
+    charging: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Actions {
+    extra_policy: Vec<PolicyBound>,
+    ignore_policy: Vec<i32>,
+    override_target_fps: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+struct PolicyBound {
+    policy: i32,
+    rel_to: Option<i32>,
+    min: Option<isize>,
+    max: Option<isize>,
+}
+
+// What the looper knows at the time of evaluation.
+pub struct Facts<'a> {
+    pub package: Option<&'a str>,
+    pub mode: Mode,
+    pub target_fps: Option<u32>,
+    pub temperature: u64,
+    // What the `target_fps_override` node holds right now
+    pub target_fps_override: Option<u32>,
+}
+
+impl Conditions {
+    fn matches(&self, facts: &Facts, charging: bool) -> bool {
+        self.package.as_ref().is_none_or(|package| {
+            facts
+                .package
+                .is_some_and(|pkg| package.contains(&pkg.to_string()))
+        }) && self
+            .mode
+            .as_ref()
+            .is_none_or(|mode| mode.contains(&facts.mode.to_string()))
+            && self.target_fps.as_ref().is_none_or(|target_fps| {
+                facts
+                    .target_fps
+                    .is_some_and(|fps| target_fps.contains(&fps))
+            })
+            && self
+                .temperature_above
+                .is_none_or(|temperature| facts.temperature > temperature)
+            && self
+                .temperature_below
+                .is_none_or(|temperature| facts.temperature < temperature)
+            && self.charging.is_none_or(|expected| expected == charging)
+    }
+}
+
+// The combined actions of all matching rules, later rules win on the same policy.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Applied {
+    bounds: BTreeMap<i32, PolicyBound>,
+    ignored: BTreeSet<i32>,
+    target_fps: Option<u32>,
+}
+
+impl Applied {
+    fn add(&mut self, actions: &Actions) {
+        for bound in &actions.extra_policy {
+            self.bounds.insert(bound.policy, *bound);
+        }
+
+        self.ignored.extend(actions.ignore_policy.iter().copied());
+
+        if actions.override_target_fps.is_some() {
+            self.target_fps = actions.override_target_fps;
+        }
+    }
+}
+
+pub struct Rules {
+    rules: Vec<Rule>,
+    modified: Option<SystemTime>,
+    applied: Applied,
+    last_eval: Instant,
+    requester: Requester,
+}
+
+impl Rules {
+    pub fn new(requester: Requester) -> Self {
+        Self {
+            rules: Vec::new(),
+            modified: None,
+            applied: Applied::default(),
+            last_eval: Instant::now(),
+            requester,
+        }
+    }
+
+    // Lets the looper gather the facts only when they are going to be used. Without any rule
+    // there is nothing to evaluate, unless what the last ones applied still has to be undone.
+    pub fn due(&mut self) -> bool {
+        if self.last_eval.elapsed() < EVAL_INTERVAL {
+            return false;
+        }
+
+        self.last_eval = Instant::now();
+        self.reload();
+        !self.rules.is_empty() || self.applied != Applied::default()
+    }
+
+    pub fn uses_temperature(&self) -> bool {
+        self.rules.iter().any(|rule| {
+            rule.when.temperature_above.is_some() || rule.when.temperature_below.is_some()
+        })
+    }
+
+    // Level triggered, whatever stops matching is undone.
+    pub fn update(&mut self, facts: &Facts) {
+        let charging = self.rules.iter().any(|rule| rule.when.charging.is_some()) && is_charging();
+        let mut wanted = Applied::default();
+        for rule in self
+            .rules
+            .iter()
+            .filter(|rule| rule.when.matches(facts, charging))
+        {
+            wanted.add(&rule.then);
+        }
+
+        if wanted != self.applied {
+            self.apply(wanted, facts);
+        }
+    }
+
+    fn reload(&mut self) {
+        let modified = fs::metadata(RULES_PATH)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        if modified == self.modified {
+            return;
+        }
+
+        self.modified = modified;
+        if modified.is_none() {
+            self.rules.clear();
+            return;
+        }
+
+        // A broken file keeps the rules that were loaded before
+        match read_rules() {
+            Ok(file) => {
+                self.rules = file.rules;
+                self.rules.retain(|rule| has_valid_modes(&rule.when));
+                for rule in &mut self.rules {
+                    validate(&mut rule.then);
+                }
+
+                info!("Loaded {} rule(s) from {RULES_PATH}", self.rules.len());
+            }
+            Err(e) => error!("Failed to parse {RULES_PATH}: {e:#}"),
+        }
+    }
+
+    fn apply(&mut self, wanted: Applied, facts: &Facts) {
+        for policy in self
+            .applied
+            .bounds
+            .keys()
+            .filter(|policy| !wanted.bounds.contains_key(policy))
+        {
+            helper_funs::remove_extra_policy(*policy);
+        }
+
+        for bound in wanted
+            .bounds
+            .values()
+            .filter(|bound| self.applied.bounds.get(&bound.policy) != Some(bound))
+        {
+            match bound.rel_to {
+                Some(rel_to) => {
+                    helper_funs::set_extra_policy_rel(bound.policy, rel_to, bound.min, bound.max);
+                }
+                None => helper_funs::set_extra_policy_abs(bound.policy, bound.min, bound.max),
+            }
+        }
+
+        for policy in self.applied.ignored.difference(&wanted.ignored) {
+            helper_funs::set_ignore_policy(*policy, false);
+        }
+
+        for policy in wanted.ignored.difference(&self.applied.ignored) {
+            helper_funs::set_ignore_policy(*policy, true);
+        }
+
+        // Only take back our own override, someone else may have set another one since
+        if wanted.target_fps != self.applied.target_fps
+            && (wanted.target_fps.is_some() || facts.target_fps_override == self.applied.target_fps)
+        {
+            helper_funs::override_target_fps(&self.requester, wanted.target_fps);
+        }
+
+        self.applied = wanted;
+    }
+}
+
+// The helpers panic on policies that do not exist, a typo in the rules must not take down the
+// scheduler.
+fn validate(actions: &mut Actions) {
+    let has_policy = |policy: i32| {
+        EXTRA_POLICY_MAP
+            .get()
+            .is_some_and(|map| map.contains_key(&policy))
+    };
+
+    actions.extra_policy.retain(|bound| {
+        let valid = has_policy(bound.policy) && bound.rel_to.is_none_or(has_policy);
+        if !valid {
+            warn!("Rule refers to a missing cpu policy, dropping {bound:?}");
+        }
+        valid
+    });
+
+    actions.ignore_policy.retain(|policy| {
+        let valid = IGNORE_MAP.get().is_some_and(|map| map.contains_key(policy));
+        if !valid {
+            warn!("Rule refers to a missing cpu policy, dropping ignore_policy {policy}");
+        }
+        valid
+    });
+}
+
+// A mistyped mode would quietly keep the rule from ever applying.
+fn has_valid_modes(conditions: &Conditions) -> bool {
+    let unknown: Vec<_> = conditions
+        .mode
+        .iter()
+        .flat_map(OneOrMany::iter)
+        .filter(|mode| mode.parse::<Mode>().is_err())
+        .collect();
+
+    if !unknown.is_empty() {
+        warn!("Rule refers to unknown mode(s) {unknown:?}, dropping it");
+    }
+    unknown.is_empty()
+}
+
+fn read_rules() -> Result<RuleFile> {
+    Ok(toml::from_str(&fs::read_to_string(RULES_PATH)?)?)
+}
+
+fn is_charging() -> bool {
+    fs::read_to_string(CHARGING_STATUS)
+        .is_ok_and(|status| matches!(status.trim(), "Charging" | "Full"))
+}
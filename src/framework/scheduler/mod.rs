@@ -67,8 +67,8 @@ impl Scheduler {
     }
 
     pub fn start_run(self) -> Result<()> {
-        let extension = Extension::init()?;
-        let config = self.config.ok_or(Error::SchedulerMissing("Config"))?;
+        let mut config = self.config.ok_or(Error::SchedulerMissing("Config"))?;
+        let extension = Extension::init(config.extension_sandbox(), config.extension_settings())?;
 
         let controller = self
             .controller
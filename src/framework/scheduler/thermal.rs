@@ -21,10 +21,15 @@ use anyhow::Result;
 #[cfg(debug_assertions)]
 use log::debug;
 
-use crate::{Config, Mode, framework::config::TemperatureThreshold};
+use crate::{
+    Config, Extension, Mode,
+    api::{snapshot::SNAPSHOT, trigger_thermal_offset_change},
+    framework::config::TemperatureThreshold,
+};
 
 pub struct Thermal {
     target_fps_offset: f64,
+    reported_offset: i64,
     core_temperature: u64,
     nodes: Vec<PathBuf>,
 }
@@ -48,12 +53,18 @@ impl Thermal {
 
         Ok(Self {
             target_fps_offset: 0.0,
+            reported_offset: 0,
             core_temperature: 0,
             nodes,
         })
     }
 
-    pub fn target_fps_offset(&mut self, config: &mut Config, mode: Mode) -> f64 {
+    pub fn target_fps_offset(
+        &mut self,
+        config: &mut Config,
+        mode: Mode,
+        extension: &Extension,
+    ) -> f64 {
         let target_core_temperature = match config.mode_config(mode).core_temp_thresh {
             TemperatureThreshold::Disabled => u64::MAX,
             TemperatureThreshold::Temp(t) => t,
@@ -73,10 +84,21 @@ impl Thermal {
             self.target_fps_offset += 0.1;
         }
 
+        // Only the part that actually throttles, in whole fps, is worth telling extensions
+        let offset = self.target_fps_offset.min(0.0).round() as i64;
+        if offset != self.reported_offset {
+            self.reported_offset = offset;
+            trigger_thermal_offset_change(extension, offset as f64, self.core_temperature);
+        }
+
         self.target_fps_offset
     }
 
-    fn temperature_update(&mut self) {
+    pub const fn core_temperature(&self) -> u64 {
+        self.core_temperature
+    }
+
+    pub fn temperature_update(&mut self) {
         self.core_temperature = self
             .nodes
             .iter()
@@ -84,5 +106,6 @@ impl Thermal {
             .map(|temp| temp.trim().parse::<u64>().unwrap_or_default())
             .max()
             .unwrap_or_default();
+        SNAPSHOT.write().core_temperature = self.core_temperature;
     }
 }
@@ -24,33 +24,35 @@ const REFRESH_TIME: Duration = Duration::from_secs(1);
 #[derive(Default)]
 struct WindowsInfo {
     pub visible_freeform_window: bool,
+    pub package: Option<String>,
     pub pids: Vec<i32>,
 }
 
 impl WindowsInfo {
     pub fn new(dump: &str) -> Self {
-        let pids = Self::parse_top_app(dump);
+        let package = Self::parse_focused_package(dump);
+        let pids = package
+            .as_deref()
+            .map_or_else(Vec::new, |package| Self::parse_top_app(dump, package));
         let visible_freeform_window = dump.contains("freeform")
             || dump.contains("FlexibleTaskCaptionView")
             || dump.contains("FlexibleTaskIndicatorView");
 
         Self {
             visible_freeform_window,
+            package,
             pids,
         }
     }
 
-    fn parse_top_app(dump: &str) -> Vec<i32> {
-        let Some(focused_app_line) = dump
+    fn parse_focused_package(dump: &str) -> Option<String> {
+        let focused_app_line = dump
             .lines()
-            .find(|line| line.trim().starts_with("mFocusedApp="))
-        else {
-            return Vec::new();
-        };
-        let Some(package_name) = Self::extract_package_name(focused_app_line) else {
-            return Vec::new();
-        };
+            .find(|line| line.trim().starts_with("mFocusedApp="))?;
+        Self::extract_package_name(focused_app_line).map(ToString::to_string)
+    }
 
+    fn parse_top_app(dump: &str, package_name: &str) -> Vec<i32> {
         // Try modern parser, if it fails, fall back to legacy parser.
         let pid = Self::parse_a16_format(dump, package_name)
             .or_else(|| Self::parse_a15_format(dump, package_name));
@@ -143,6 +145,11 @@ impl TopAppsWatcher {
         &self.cache().pids
     }
 
+    pub fn top_app(&mut self) -> Option<(i32, &str)> {
+        let cache = self.cache();
+        Some((*cache.pids.first()?, cache.package.as_deref()?))
+    }
+
     pub fn visible_freeform_window(&mut self) -> bool {
         self.cache().visible_freeform_window
     }
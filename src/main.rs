@@ -31,9 +31,14 @@ mod framework;
 mod misc;
 
 use std::{
-    env, fs,
+    env,
+    fs::{self, File},
     io::{self, prelude::*},
-    process,
+    mem,
+    os::fd::FromRawFd,
+    process, ptr,
+    sync::atomic::{AtomicI32, Ordering},
+    thread,
 };
 
 use framework::prelude::*;
@@ -73,15 +78,18 @@ fn main() -> Result<()> {
             }
             error!("{:#?}", e.backtrace());
         });
+        api::sysfs::restore_nodes();
+    } else if args[1] == "ext-test" {
+        start_logger()?;
+        ext_test(&args[2], &args[3])?;
+    } else if args[1] == "ext-trust" {
+        ext_trust(&args[2])?;
     }
 
     Ok(())
 }
 
-fn run<S>(std_path: S) -> Result<()>
-where
-    S: AsRef<str>,
-{
+fn start_logger() -> Result<()> {
     #[cfg(not(debug_assertions))]
     let logger_spec = LogSpecification::info();
 
@@ -93,6 +101,16 @@ where
         .format(log_format)
         .start()?;
 
+    Ok(())
+}
+
+fn run<S>(std_path: S) -> Result<()>
+where
+    S: AsRef<str>,
+{
+    start_logger()?;
+    restore_nodes_on_signal()?;
+
     let std_path = std_path.as_ref();
 
     let self_pid = process::id();
@@ -112,6 +130,56 @@ where
     Ok(())
 }
 
+// Write end of the pipe `on_signal` wakes the signal thread through.
+static SIGNAL_PIPE: AtomicI32 = AtomicI32::new(-1);
+
+extern "C" fn on_signal(signal: libc::c_int) {
+    // Nothing but `write` is safe in here
+    let byte = signal as u8;
+    unsafe {
+        libc::write(
+            SIGNAL_PIPE.load(Ordering::Relaxed),
+            (&raw const byte).cast(),
+            1,
+        );
+    }
+}
+
+// Restores the nodes written by extensions when fas-rs is stopped, not only when `run` fails.
+// A handler rather than a blocked mask, which children started by extensions would inherit.
+fn restore_nodes_on_signal() -> Result<()> {
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    SIGNAL_PIPE.store(fds[1], Ordering::Relaxed);
+    let mut pipe = unsafe { File::from_raw_fd(fds[0]) };
+
+    for signal in [libc::SIGTERM, libc::SIGINT] {
+        unsafe {
+            let mut action: libc::sigaction = mem::zeroed();
+            action.sa_sigaction = on_signal as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESTART;
+            if libc::sigaction(signal, &raw const action, ptr::null_mut()) != 0 {
+                return Err(io::Error::last_os_error().into());
+            }
+        }
+    }
+
+    thread::Builder::new()
+        .name("SignalThread".into())
+        .spawn(move || {
+            let mut signal = [0];
+            if pipe.read_exact(&mut signal).is_ok() {
+                warn!("Got signal {}, exiting", signal[0]);
+                api::sysfs::restore_nodes();
+                process::exit(0);
+            }
+        })?;
+
+    Ok(())
+}
+
 fn log_format(
     write: &mut dyn Write,
     now: &mut DeferredNow,
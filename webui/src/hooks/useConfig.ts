@@ -13,6 +13,8 @@ import {
   PowerModes,
   FpsValue,
   PowerSettings,
+  ExtensionSettings,
+  GameStrategy,
 } from "@/types/config";
 import { toast } from "sonner";
 import { exec } from "../lib/kernelsu";
@@ -82,6 +84,8 @@ export function useConfig() {
     useState<ConfigOptions>(defaultConfig);
   const [gameList, setGameList] = useState<GameList>(defaultGameList);
   const [powerModes, setPowerModes] = useState<PowerModes>(defaultPowerModes);
+  const [extensions, setExtensions] = useState<ExtensionSettings>({});
+  const [gameStrategy, setGameStrategy] = useState<GameStrategy>({});
   const [language, setLanguage] = useState<"en" | "zh">("en");
 
   useEffect(() => {
@@ -91,6 +95,8 @@ export function useConfig() {
         setConfigOptions(configData.configOptions || defaultConfig);
         setGameList(configData.gameList || defaultGameList);
         setPowerModes(configData.powerModes || defaultPowerModes);
+        setExtensions(configData.extensions || {});
+        setGameStrategy(configData.gameStrategy || {});
       } catch (_error) {
         toast.error("Failed to load configuration");
       }
@@ -230,17 +236,21 @@ export function useConfig() {
         configOptions,
         gameList,
         powerModes,
+        extensions,
+        gameStrategy,
       });
       toast.success("Configuration saved successfully!");
     } catch (error) {
       toast.error("Failed to save configuration: " + error);
     }
-  }, [configOptions, gameList, powerModes]);
+  }, [configOptions, gameList, powerModes, extensions, gameStrategy]);
 
   const readConfig = async (): Promise<{
     configOptions: ConfigOptions;
     gameList: GameList;
     powerModes: PowerModes;
+    extensions: ExtensionSettings;
+    gameStrategy: GameStrategy;
   }> => {
     if (process.env.NODE_ENV === "development") {
       return {
@@ -259,6 +269,8 @@ export function useConfig() {
           performance: { margin_fps: 0.3, core_temp_thresh: 95000 },
           fast: { margin_fps: 0, core_temp_thresh: 95000 },
         },
+        extensions: {},
+        gameStrategy: {},
       };
     }
 
@@ -282,6 +294,8 @@ export function useConfig() {
       balance: PowerSettings;
       performance: PowerSettings;
       fast: PowerSettings;
+      extensions?: ExtensionSettings;
+      game_strategy?: GameStrategy;
     };
 
     return {
@@ -293,6 +307,8 @@ export function useConfig() {
         performance: configRaw.performance,
         fast: configRaw.fast,
       },
+      extensions: configRaw.extensions || {},
+      gameStrategy: configRaw.game_strategy || {},
     };
   };
 
@@ -300,6 +316,8 @@ export function useConfig() {
     configOptions: ConfigOptions;
     gameList: GameList;
     powerModes: PowerModes;
+    extensions: ExtensionSettings;
+    gameStrategy: GameStrategy;
   }): Promise<void> => {
     if (process.env.NODE_ENV === "development") {
       console.log("Development mode: Skipping actual config write");
@@ -314,6 +332,12 @@ export function useConfig() {
         balance: data.powerModes.balance,
         performance: data.powerModes.performance,
         fast: data.powerModes.fast,
+        ...(Object.keys(data.extensions).length > 0
+          ? { extensions: data.extensions as TOML.JsonMap }
+          : {}),
+        ...(Object.keys(data.gameStrategy).length > 0
+          ? { game_strategy: data.gameStrategy }
+          : {}),
       })
         .replace(/\[\s+/g, "[")
         .replace(/\s+\]/g, "]");
@@ -1,14 +1,35 @@
 export type ConfigOptions = {
   keep_std: boolean;
   scene_game_list: boolean;
+  extension_memory_limit?: number;
+  extension_callback_timeout?: number;
+  extension_max_failures?: number;
   language: "en" | "zh";
 };
 
 export type PowerSettings = {
   margin_fps: number;
   core_temp_thresh: number | "disabled";
+  revert_after?: number | "game" | "never";
+  kp?: number;
+  ki?: number;
+  kd?: number;
+  strategy?: ControlStrategy;
+  steps?: [number, number][];
+  jank?: JankSettings;
 };
 
+export type JankSettings = {
+  fps_below?: number;
+  low_1?: number;
+  low_01?: number;
+  over_1_5x?: number;
+  over_2x?: number;
+  variance?: number;
+};
+
+export type ControlStrategy = "p" | "pid" | "step";
+
 export type UpdatePowerModeFn = (
   mode: keyof PowerModes,
   setting: keyof PowerSettings,
@@ -27,3 +48,13 @@ export type FpsValue = number | number[];
 export type GameList = {
   [packageName: string]: FpsValue;
 };
+
+// `[extensions.<name>]`, not edited here but kept when saving
+export type ExtensionSettings = {
+  [extension: string]: Record<string, unknown>;
+};
+
+// `[game_strategy]`, likewise only kept when saving
+export type GameStrategy = {
+  [packageName: string]: ControlStrategy;
+};